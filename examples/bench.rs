@@ -10,9 +10,9 @@ enum Cmd {
 }
 
 async fn worker(channel: Channel<Option<Cmd>>) {
-    while let Some(command) = channel.recv().await {
+    while let Some(command) = channel.recv().await.unwrap() {
         match command {
-            Cmd::Cos(a, s) => s.send(libm::cosf(a)).await,
+            Cmd::Cos(a, s) => s.send(libm::cosf(a)).await.unwrap(),
         }
     }
 }
@@ -20,7 +20,7 @@ async fn worker(channel: Channel<Option<Cmd>>) {
 async fn worker_flume(tasker: flume::Receiver<Cmd>) {
     while let Ok(command) = tasker.recv_async().await {
         match command {
-            Cmd::Cos(a, s) => s.send(libm::cosf(a)).await,
+            Cmd::Cos(a, s) => s.send(libm::cosf(a)).await.unwrap(),
         }
     }
 }
@@ -36,19 +36,25 @@ async fn tasker_multi() {
 
     let channel = Channel::new();
     for _ in 1..=1024 {
-        worker.send(Some(Cmd::Cos(750.0, channel.clone()))).await;
-        channel.recv().await;
+        worker
+            .send(Some(Cmd::Cos(750.0, channel.clone())))
+            .await
+            .unwrap();
+        channel.recv().await.unwrap();
     }
     let now = Instant::now();
     for _ in 1..=1024 * 256 {
-        worker.send(Some(Cmd::Cos(750.0, channel.clone()))).await;
-        channel.recv().await;
+        worker
+            .send(Some(Cmd::Cos(750.0, channel.clone())))
+            .await
+            .unwrap();
+        channel.recv().await.unwrap();
     }
     let elapsed = now.elapsed() / (1024 * 256);
     println!("Whisk (2-thread): {:?}", elapsed);
 
     // Tell worker to stop
-    worker.send(None).await;
+    worker.send(None).await.unwrap();
     worker_thread.join().unwrap();
 }
 
@@ -61,27 +67,33 @@ async fn tasker_single(executor: &Executor) {
         let join = join.clone();
         async move {
             worker(tasker).await;
-            join.send(()).await;
+            join.send(()).await.unwrap();
         }
     });
     let worker = chan;
 
     let channel = Channel::new();
     for _ in 1..=1024 {
-        worker.send(Some(Cmd::Cos(750.0, channel.clone()))).await;
-        channel.recv().await;
+        worker
+            .send(Some(Cmd::Cos(750.0, channel.clone())))
+            .await
+            .unwrap();
+        channel.recv().await.unwrap();
     }
     let now = Instant::now();
     for _ in 1..=1024 * 256 {
-        worker.send(Some(Cmd::Cos(750.0, channel.clone()))).await;
-        channel.recv().await;
+        worker
+            .send(Some(Cmd::Cos(750.0, channel.clone())))
+            .await
+            .unwrap();
+        channel.recv().await.unwrap();
     }
     let elapsed = now.elapsed() / (1024 * 256);
     println!("Whisk (1-thread): {:?}", elapsed);
 
     // Tell worker to stop
-    worker.send(None).await;
-    join.recv().await;
+    worker.send(None).await.unwrap();
+    join.recv().await.unwrap();
 }
 
 async fn flume_multi() {
@@ -97,7 +109,7 @@ async fn flume_multi() {
             .send_async(Cmd::Cos(750.0, channel.clone()))
             .await
             .unwrap();
-        channel.recv().await;
+        channel.recv().await.unwrap();
     }
     let now = Instant::now();
     for _ in 1..=1024 * 256 {
@@ -105,7 +117,7 @@ async fn flume_multi() {
             .send_async(Cmd::Cos(750.0, channel.clone()))
             .await
             .unwrap();
-        channel.recv().await;
+        channel.recv().await.unwrap();
     }
     let elapsed = now.elapsed() / (1024 * 256);
     println!("Flume (2-thread): {:?}", elapsed);
@@ -123,7 +135,7 @@ async fn flume_single(executor: &Executor) {
         let join = join.clone();
         async move {
             worker_flume(tasker).await;
-            join.send(()).await;
+            join.send(()).await.unwrap();
         }
     });
 
@@ -133,7 +145,7 @@ async fn flume_single(executor: &Executor) {
             .send_async(Cmd::Cos(750.0, channel.clone()))
             .await
             .unwrap();
-        channel.recv().await;
+        channel.recv().await.unwrap();
     }
     let now = Instant::now();
     for _ in 1..=1024 * 256 {
@@ -141,14 +153,14 @@ async fn flume_single(executor: &Executor) {
             .send_async(Cmd::Cos(750.0, channel.clone()))
             .await
             .unwrap();
-        channel.recv().await;
+        channel.recv().await.unwrap();
     }
     let elapsed = now.elapsed() / (1024 * 256);
     println!("Flume (1-thread): {:?}", elapsed);
 
     // Tell worker to stop
     drop(worker);
-    join.recv().await;
+    join.recv().await.unwrap();
 }
 
 async fn dyn_lib() {
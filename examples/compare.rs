@@ -0,0 +1,350 @@
+//! Compares whisk with flume, async-channel, kanal and tokio's channels on the
+//! same workloads: oneshot replies, a single producer and consumer, many
+//! producers with a single consumer, and many producers with many consumers
+//! (which tokio's channels don't support).
+//!
+//! Pass the number of messages per producer as the first argument.  Run in
+//! release mode for meaningful numbers:
+//!
+//! ```sh
+//! cargo run --release --example compare
+//! ```
+
+use std::{
+    future::Future,
+    thread,
+    time::{Duration, Instant},
+};
+
+use futures::executor::block_on;
+use whisk::{Channel, Oneshot};
+
+const DEFAULT_MESSAGES: u32 = 64 * 1024;
+const CAPACITY: usize = 64;
+const PRODUCERS: u32 = 4;
+
+/// Run `f` on a new thread with its own executor
+fn spawn<F: Future<Output = ()> + Send + 'static>(
+    f: F,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || block_on(f))
+}
+
+/// Print the average time per message
+fn report(workload: &str, name: &str, elapsed: Duration, messages: u32) {
+    println!("{workload} {name}: {:?}", elapsed / messages);
+}
+
+fn oneshot(messages: u32) {
+    let now = Instant::now();
+
+    block_on(async {
+        for i in 0..messages {
+            let (oneshot, reply) = Oneshot::new();
+
+            reply.send(i).unwrap();
+            assert_eq!(oneshot.recv().await, Ok(i));
+        }
+    });
+    report("Oneshot", "whisk", now.elapsed(), messages);
+
+    let now = Instant::now();
+
+    block_on(async {
+        for i in 0..messages {
+            let (reply, oneshot) = flume::bounded(1);
+
+            reply.send(i).unwrap();
+            assert_eq!(oneshot.recv_async().await, Ok(i));
+        }
+    });
+    report("Oneshot", "flume", now.elapsed(), messages);
+
+    let now = Instant::now();
+
+    block_on(async {
+        for i in 0..messages {
+            let (reply, oneshot) = async_channel::bounded(1);
+
+            reply.send(i).await.unwrap();
+            assert_eq!(oneshot.recv().await, Ok(i));
+        }
+    });
+    report("Oneshot", "async-channel", now.elapsed(), messages);
+
+    let now = Instant::now();
+
+    block_on(async {
+        for i in 0..messages {
+            let (reply, oneshot) = kanal::bounded_async(1);
+
+            reply.send(i).await.unwrap();
+            assert_eq!(oneshot.recv().await, Ok(i));
+        }
+    });
+    report("Oneshot", "kanal", now.elapsed(), messages);
+
+    let now = Instant::now();
+
+    block_on(async {
+        for i in 0..messages {
+            let (reply, oneshot) = tokio::sync::oneshot::channel();
+
+            reply.send(i).unwrap();
+            assert_eq!(oneshot.await, Ok(i));
+        }
+    });
+    report("Oneshot", "tokio", now.elapsed(), messages);
+}
+
+fn mpsc(workload: &str, producers: u32, messages: u32) {
+    let total = producers * messages;
+
+    // whisk
+    let channel = Channel::new();
+
+    channel.set_capacity(CAPACITY);
+
+    let now = Instant::now();
+    let threads: Vec<_> = (0..producers)
+        .map(|_| {
+            let channel = channel.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    channel.send(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    block_on(async {
+        for _ in 0..total {
+            channel.recv().await.unwrap();
+        }
+    });
+    threads.into_iter().for_each(|t| t.join().unwrap());
+    report(workload, "whisk", now.elapsed(), total);
+
+    // flume
+    let (sender, receiver) = flume::bounded(CAPACITY);
+    let now = Instant::now();
+    let threads: Vec<_> = (0..producers)
+        .map(|_| {
+            let sender = sender.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    sender.send_async(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    block_on(async {
+        for _ in 0..total {
+            receiver.recv_async().await.unwrap();
+        }
+    });
+    threads.into_iter().for_each(|t| t.join().unwrap());
+    report(workload, "flume", now.elapsed(), total);
+
+    // async-channel
+    let (sender, receiver) = async_channel::bounded(CAPACITY);
+    let now = Instant::now();
+    let threads: Vec<_> = (0..producers)
+        .map(|_| {
+            let sender = sender.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    sender.send(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    block_on(async {
+        for _ in 0..total {
+            receiver.recv().await.unwrap();
+        }
+    });
+    threads.into_iter().for_each(|t| t.join().unwrap());
+    report(workload, "async-channel", now.elapsed(), total);
+
+    // kanal
+    let (sender, receiver) = kanal::bounded_async(CAPACITY);
+    let now = Instant::now();
+    let threads: Vec<_> = (0..producers)
+        .map(|_| {
+            let sender = sender.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    sender.send(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    block_on(async {
+        for _ in 0..total {
+            receiver.recv().await.unwrap();
+        }
+    });
+    threads.into_iter().for_each(|t| t.join().unwrap());
+    report(workload, "kanal", now.elapsed(), total);
+
+    // tokio
+    let (sender, mut receiver) = tokio::sync::mpsc::channel(CAPACITY);
+    let now = Instant::now();
+    let threads: Vec<_> = (0..producers)
+        .map(|_| {
+            let sender = sender.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    sender.send(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    block_on(async {
+        for _ in 0..total {
+            receiver.recv().await.unwrap();
+        }
+    });
+    threads.into_iter().for_each(|t| t.join().unwrap());
+    report(workload, "tokio", now.elapsed(), total);
+}
+
+fn mpmc(threads: u32, messages: u32) {
+    let total = threads * messages;
+
+    // whisk
+    let channel = Channel::new();
+
+    channel.set_capacity(CAPACITY);
+
+    let now = Instant::now();
+    let consumers: Vec<_> = (0..threads)
+        .map(|_| {
+            let channel = channel.clone();
+
+            spawn(async move { while channel.recv().await.is_ok() {} })
+        })
+        .collect();
+    let producers: Vec<_> = (0..threads)
+        .map(|_| {
+            let channel = channel.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    channel.send(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    producers.into_iter().for_each(|t| t.join().unwrap());
+    // Let consumers drain the messages still in transit
+    channel.seal();
+    consumers.into_iter().for_each(|t| t.join().unwrap());
+    report("MPMC", "whisk", now.elapsed(), total);
+
+    // flume
+    let (sender, receiver) = flume::bounded(CAPACITY);
+    let now = Instant::now();
+    let consumers: Vec<_> = (0..threads)
+        .map(|_| {
+            let receiver = receiver.clone();
+
+            spawn(async move { while receiver.recv_async().await.is_ok() {} })
+        })
+        .collect();
+    let producers: Vec<_> = (0..threads)
+        .map(|_| {
+            let sender = sender.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    sender.send_async(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    producers.into_iter().for_each(|t| t.join().unwrap());
+    // Let consumers drain the messages still in transit
+    drop(sender);
+    consumers.into_iter().for_each(|t| t.join().unwrap());
+    report("MPMC", "flume", now.elapsed(), total);
+
+    // async-channel
+    let (sender, receiver) = async_channel::bounded(CAPACITY);
+    let now = Instant::now();
+    let consumers: Vec<_> = (0..threads)
+        .map(|_| {
+            let receiver = receiver.clone();
+
+            spawn(async move { while receiver.recv().await.is_ok() {} })
+        })
+        .collect();
+    let producers: Vec<_> = (0..threads)
+        .map(|_| {
+            let sender = sender.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    sender.send(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    producers.into_iter().for_each(|t| t.join().unwrap());
+    // Let consumers drain the messages still in transit
+    drop(sender);
+    consumers.into_iter().for_each(|t| t.join().unwrap());
+    report("MPMC", "async-channel", now.elapsed(), total);
+
+    // kanal
+    let (sender, receiver) = kanal::bounded_async(CAPACITY);
+    let now = Instant::now();
+    let consumers: Vec<_> = (0..threads)
+        .map(|_| {
+            let receiver = receiver.clone();
+
+            spawn(async move { while receiver.recv().await.is_ok() {} })
+        })
+        .collect();
+    let producers: Vec<_> = (0..threads)
+        .map(|_| {
+            let sender = sender.clone();
+
+            spawn(async move {
+                for i in 0..messages {
+                    sender.send(i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+
+    producers.into_iter().for_each(|t| t.join().unwrap());
+    // Let consumers drain the messages still in transit
+    drop(sender);
+    consumers.into_iter().for_each(|t| t.join().unwrap());
+    report("MPMC", "kanal", now.elapsed(), total);
+}
+
+fn main() {
+    let messages = std::env::args()
+        .nth(1)
+        .map_or(DEFAULT_MESSAGES, |arg| arg.parse().unwrap());
+
+    oneshot(messages);
+    mpsc("SPSC", 1, messages);
+    mpsc("MPSC", PRODUCERS, messages);
+    mpmc(PRODUCERS, messages);
+}
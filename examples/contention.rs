@@ -0,0 +1,74 @@
+//! Measures throughput as more threads contend on one channel, with the same
+//! number of producer and consumer threads.
+//!
+//! Pass the number of messages per producer as the first argument.  Compare
+//! lock implementations by enabling their features:
+//!
+//! ```sh
+//! cargo run --release --example contention
+//! cargo run --release --example contention --features ticket_lock
+//! cargo run --release --example contention --features std_lock
+//! ```
+
+use std::{thread, time::Instant};
+
+use futures::executor::block_on;
+use whisk::Channel;
+
+const THREADS: [usize; 5] = [2, 4, 8, 16, 32];
+const DEFAULT_MESSAGES: usize = 16 * 1024;
+
+fn contend(threads: usize, messages: usize) {
+    let producers = threads / 2;
+    let total = producers * messages;
+    let channel = Channel::new();
+
+    channel.set_capacity(producers);
+
+    let now = Instant::now();
+    let consumers: Vec<_> = (0..producers)
+        .map(|_| {
+            let channel = channel.clone();
+
+            thread::spawn(move || {
+                block_on(async move { while channel.recv().await.is_ok() {} })
+            })
+        })
+        .collect();
+    let producers: Vec<_> = (0..producers)
+        .map(|_| {
+            let channel = channel.clone();
+
+            thread::spawn(move || {
+                block_on(async move {
+                    for i in 0..messages {
+                        channel.send(i).await.unwrap();
+                    }
+                })
+            })
+        })
+        .collect();
+
+    producers.into_iter().for_each(|t| t.join().unwrap());
+    // Let consumers drain the messages still in transit
+    channel.seal();
+    consumers.into_iter().for_each(|t| t.join().unwrap());
+
+    let elapsed = now.elapsed();
+
+    println!(
+        "{threads} threads: {:?} per message, {:.0} messages/s",
+        elapsed / total as u32,
+        total as f64 / elapsed.as_secs_f64(),
+    );
+}
+
+fn main() {
+    let messages = std::env::args()
+        .nth(1)
+        .map_or(DEFAULT_MESSAGES, |arg| arg.parse().unwrap());
+
+    for threads in THREADS {
+        contend(threads, messages);
+    }
+}
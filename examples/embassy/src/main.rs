@@ -0,0 +1,42 @@
+//! Use whisk with the embassy executor.  Targets embassy's `arch-std` executor
+//! so it runs on a desktop, but a `static` [`Queue`] works the same way on
+//! single-core embedded executors, with no pinning or `Send` requirements.
+//!
+//! ```shell
+//! cargo run
+//! ```
+
+use embassy_executor::Spawner;
+use embassy_time::Timer;
+use whisk::Queue;
+
+/// Queue of sensor readings, shared between tasks without an `Arc`
+static READINGS: Queue<u32> = Queue::new();
+
+#[embassy_executor::task]
+async fn sensor() {
+    for reading in 0..4 {
+        Timer::after_millis(100).await;
+        println!("Sending reading {reading}");
+        READINGS.send(reading).await.unwrap();
+    }
+
+    // Let the logger drain the remaining readings
+    READINGS.seal();
+}
+
+#[embassy_executor::task]
+async fn logger() {
+    while let Ok(reading) = READINGS.recv().await {
+        println!("Received reading {reading}");
+    }
+
+    println!("Sensor finished");
+    std::process::exit(0);
+}
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    spawner.spawn(sensor()).unwrap();
+    spawner.spawn(logger()).unwrap();
+}
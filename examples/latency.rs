@@ -0,0 +1,68 @@
+//! Measures the latency of cross-thread wakeups, from sending a message to a
+//! waiting receiver on another thread to that receiver resuming with it, and
+//! prints percentiles rather than the mean.
+//!
+//! Messages are sent at an interval, so the receiver is usually parked when
+//! one arrives.  Pass the number of messages as the first argument.
+//!
+//! ```sh
+//! cargo run --release --example latency
+//! ```
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use futures::executor::block_on;
+use whisk::Channel;
+
+const DEFAULT_MESSAGES: usize = 16 * 1024;
+const INTERVAL: Duration = Duration::from_micros(50);
+const PERCENTILES: [f64; 4] = [50.0, 99.0, 99.9, 100.0];
+
+fn main() {
+    let messages = std::env::args()
+        .nth(1)
+        .map_or(DEFAULT_MESSAGES, |arg| arg.parse().unwrap());
+    let channel = Channel::<Instant>::new();
+    let receiver = thread::spawn({
+        let channel = channel.clone();
+
+        move || {
+            block_on(async move {
+                let mut latencies = Vec::with_capacity(messages);
+
+                while let Ok(sent) = channel.recv().await {
+                    latencies.push(sent.elapsed());
+                }
+
+                latencies
+            })
+        }
+    });
+
+    block_on(async {
+        for _ in 0..messages {
+            let next = Instant::now() + INTERVAL;
+
+            channel.send(Instant::now()).await.unwrap();
+
+            // Busy wait, since sleeping is too coarse for short intervals
+            while Instant::now() < next {
+                std::hint::spin_loop();
+            }
+        }
+    });
+    channel.seal();
+
+    let mut latencies = receiver.join().unwrap();
+
+    latencies.sort_unstable();
+
+    for percentile in PERCENTILES {
+        let index = (latencies.len() - 1) as f64 * percentile / 100.0;
+
+        println!("p{percentile}: {:?}", latencies[index as usize]);
+    }
+}
@@ -15,19 +15,19 @@ fn main() {
         std::thread::spawn(|| {
             pasts::Executor::default().block_on(async move {
                 println!("Sending...");
-                channel.send(Some(1)).await;
+                channel.send(Some(1)).await.unwrap();
                 let weak: Weak<Queue<_>> = Arc::downgrade(&channel.into());
                 if Weak::strong_count(&weak) == 1
                     && ONCE.fetch_and(false, Ordering::Relaxed)
                 {
-                    weak.upgrade().unwrap().send(None).await;
+                    weak.upgrade().unwrap().send(None).await.unwrap();
                 }
             })
         });
     }
     executor.block_on(async move {
         let mut c = 0;
-        while let Some(v) = channel.recv().await {
+        while let Some(v) = channel.recv().await.unwrap() {
             println!("Received one.");
             c += v;
         }
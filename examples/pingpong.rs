@@ -0,0 +1,46 @@
+//! Measures the uncontended round trip, where a sender and receiver alternate
+//! on one channel but never access it at the same time.
+
+use std::time::Instant;
+
+use pasts::Executor;
+use whisk::Channel;
+
+const WARMUP: u32 = 1024;
+const ROUND_TRIPS: u32 = 1024 * 1024;
+
+async fn ping(ping: Channel<u32>, pong: Channel<u32>, round_trips: u32) {
+    for i in 0..round_trips {
+        ping.send(i).await.unwrap();
+        assert_eq!(pong.recv().await, Ok(i));
+    }
+}
+
+async fn pong(ping: Channel<u32>, pong: Channel<u32>, round_trips: u32) {
+    for _ in 0..round_trips {
+        pong.send(ping.recv().await.unwrap()).await.unwrap();
+    }
+}
+
+async fn round_trips(round_trips: u32) {
+    let (a, b) = (Channel::new(), Channel::new());
+
+    futures::join!(
+        ping(a.clone(), b.clone(), round_trips),
+        pong(a, b, round_trips),
+    );
+}
+
+fn main() {
+    Executor::default().block_on(async {
+        round_trips(WARMUP).await;
+
+        let now = Instant::now();
+
+        round_trips(ROUND_TRIPS).await;
+
+        let elapsed = now.elapsed() / ROUND_TRIPS;
+
+        println!("Whisk ping-pong (1-thread): {:?}", elapsed);
+    });
+}
@@ -5,11 +5,11 @@ enum Cmd {
     Add(u32, u32, Channel<u32>),
 }
 
-async fn worker(channel: Channel<Option<Cmd>>) {
-    while let Some(command) = channel.recv().await {
+async fn worker(channel: Channel<Cmd>) {
+    while let Ok(command) = channel.recv().await {
         println!("Worker receiving command");
         match command {
-            Cmd::Add(a, b, s) => s.send(a + b).await,
+            Cmd::Add(a, b, s) => s.send(a + b).await.unwrap(),
         }
     }
 
@@ -30,15 +30,18 @@ async fn main(_spawner: impl async_main::Spawn) {
     let oneshot = Channel::new();
     for _ in 0..32 {
         println!("Sending command…");
-        channel.send(Some(Cmd::Add(43, 400, oneshot.clone()))).await;
+        channel
+            .send(Cmd::Add(43, 400, oneshot.clone()))
+            .await
+            .unwrap();
         println!("Receiving response…");
-        let response = oneshot.recv().await;
+        let response = oneshot.recv().await.unwrap();
         assert_eq!(response, 443);
     }
 
     // Tell worker to stop
     println!("Stopping worker…");
-    channel.send(None).await;
+    channel.close();
     println!("Waiting for worker to stop…");
 
     worker_thread.join().unwrap();
@@ -0,0 +1,102 @@
+//! Sends millions of messages between producer and consumer threads, asserting
+//! that every message is received exactly once, and that each consumer
+//! receives any one producer's messages in the order they were sent.
+//!
+//! Pass the number of messages per producer as the first argument.  To check
+//! for data races, run under ThreadSanitizer:
+//!
+//! ```sh
+//! RUSTFLAGS="-Zsanitizer=thread" cargo +nightly run -Zbuild-std \
+//!     --target x86_64-unknown-linux-gnu --example stress
+//! ```
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use futures::executor::block_on;
+use whisk::Channel;
+
+const CAPACITIES: [usize; 2] = [1, 16];
+const DEFAULT_MESSAGES: usize = 1024 * 1024;
+
+struct Seen {
+    flags: Vec<AtomicBool>,
+    count: AtomicUsize,
+}
+
+fn stress(threads: usize, messages: usize, capacity: usize) {
+    let total = threads * messages;
+    let channel = Channel::new();
+    let seen = Arc::new(Seen {
+        flags: (0..total).map(|_| AtomicBool::new(false)).collect(),
+        count: AtomicUsize::new(0),
+    });
+
+    channel.set_capacity(capacity);
+
+    let producers = (0..threads).map(|producer| {
+        let channel = channel.clone();
+
+        thread::spawn(move || {
+            block_on(async move {
+                for i in 0..messages {
+                    channel.send(producer * messages + i).await.unwrap();
+                }
+            })
+        })
+    });
+    let producers: Vec<_> = producers.collect();
+    let consumers = (0..threads).map(|_| {
+        let (channel, seen) = (channel.clone(), seen.clone());
+
+        thread::spawn(move || {
+            block_on(async move {
+                let mut last = vec![None; threads];
+
+                while let Ok(message) = channel.recv().await {
+                    let last = &mut last[message / messages];
+
+                    assert!(!seen.flags[message].swap(true, Ordering::Relaxed));
+                    assert!(*last < Some(message));
+                    *last = Some(message);
+
+                    // Close once the last message is seen
+                    if seen.count.fetch_add(1, Ordering::Relaxed) + 1 == total {
+                        channel.close();
+                    }
+                }
+            })
+        })
+    });
+    let consumers: Vec<_> = consumers.collect();
+
+    for thread in producers.into_iter().chain(consumers) {
+        thread.join().unwrap();
+    }
+
+    assert_eq!(seen.count.load(Ordering::Relaxed), total);
+    assert!(seen.flags.iter().all(|flag| flag.load(Ordering::Relaxed)));
+}
+
+fn main() {
+    let messages = std::env::args()
+        .nth(1)
+        .map_or(DEFAULT_MESSAGES, |arg| arg.parse().unwrap());
+    let threads = thread::available_parallelism().map_or(4, |n| n.get());
+    let threads = threads.max(2);
+
+    for capacity in CAPACITIES {
+        println!(
+            "{threads} producers and consumers, {messages} messages each, \
+             capacity {capacity}…"
+        );
+        stress(threads, messages, capacity);
+    }
+
+    println!("All messages received exactly once");
+}
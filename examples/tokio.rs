@@ -53,9 +53,9 @@ async fn main() {
     let channel_clone = channel.clone();
     let join_handle = task::spawn(async move { worker(channel_clone).await });
 
-    channel.send(Some(0)).await;
-    channel.send(Some(12)).await;
-    channel.send(None).await;
+    channel.send(Some(0)).await.unwrap();
+    channel.send(Some(12)).await.unwrap();
+    channel.send(None).await.unwrap();
 
     let output = join_handle.await.unwrap();
 
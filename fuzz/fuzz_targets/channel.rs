@@ -0,0 +1,12 @@
+//! Interprets the input as a sequence of operations on one channel, checking
+//! the results against a model queue, and that no waiting future is left
+//! without being woken.
+
+#![no_main]
+
+#[path = "../../tests/harness/mod.rs"]
+mod harness;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| harness::operations::run(data));
@@ -0,0 +1,573 @@
+//! Request/response actors
+//!
+//! A [`Commander`] sends [`Request`]s to a [`Messenger`], and waits for the
+//! response to each.  Commanders can be cloned to hand out the actor's
+//! address, while the messenger is owned by the actor's receive loop.
+//! Requests can also be sent on a plain [`Channel`] with
+//! [`Channel::request()`].
+//!
+//! For actors that only receive messages, implement [`Actor`] and run it on
+//! a [`Mailbox`], or spawn it with [`spawn_actor()`].
+//!
+//! ```rust
+//! use whisk::actor::{Commander, Messenger};
+//!
+//! enum Cmd {
+//!     Add(u32, u32),
+//!     Negate(i32),
+//! }
+//!
+//! async fn worker_main(messenger: Messenger<Cmd, i64>) {
+//!     while let Ok(request) = messenger.recv().await {
+//!         let answer = match *request.get() {
+//!             Cmd::Add(a, b) => i64::from(a) + i64::from(b),
+//!             Cmd::Negate(a) => -i64::from(a),
+//!         };
+//!
+//!         request.respond(answer).unwrap();
+//!     }
+//! }
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let (commander, messenger) = Commander::new();
+//!         let worker = worker_main(messenger);
+//!         let tasker = async move {
+//!             assert_eq!(commander.request(Cmd::Add(43, 400)).await, Ok(443));
+//!             assert_eq!(commander.request(Cmd::Negate(7)).await, Ok(-7));
+//!         };
+//!
+//!         futures::future::join(worker, tasker).await;
+//!     });
+//! }
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{future::Future, pin::Pin, task::Poll};
+
+use crate::{
+    Channel, Oneshot, OneshotSender, Receiver, RecvError, SendError, Sender,
+};
+
+/// A query, with the slot for its answer
+pub struct Request<Q, A> {
+    query: Q,
+    reply: OneshotSender<A>,
+}
+
+impl<Q, A> Request<Q, A> {
+    /// Create a request for `query`, with the receiver for its answer
+    pub(crate) fn new(query: Q) -> (Self, Oneshot<A>) {
+        let (oneshot, reply) = Oneshot::new();
+
+        (Self { query, reply }, oneshot)
+    }
+
+    /// Get the query.
+    pub fn get(&self) -> &Q {
+        &self.query
+    }
+
+    /// Answer the query, consuming this request.
+    ///
+    /// Returns the answer back as an error if the requester stopped waiting.
+    pub fn respond(self, answer: A) -> Result<(), SendError<A>> {
+        self.reply.send(answer)
+    }
+
+    /// Split into the query and the sender for its answer.
+    pub fn into_parts(self) -> (Q, OneshotSender<A>) {
+        (self.query, self.reply)
+    }
+}
+
+impl<Q, A> core::fmt::Debug for Request<Q, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Request").finish_non_exhaustive()
+    }
+}
+
+/// Handle for sending requests to an actor
+///
+/// Once every commander is dropped, the messenger's `recv()` returns
+/// [`RecvError`] after the remaining requests are received.
+pub struct Commander<Q, A>(Channel<Request<Q, A>>);
+
+impl<Q, A> Commander<Q, A> {
+    /// Create a new actor connection, returning the requesting and handling
+    /// halves.
+    pub fn new() -> (Self, Messenger<Q, A>) {
+        let channel = Channel::new();
+
+        (Self(channel.clone()), Messenger(channel))
+    }
+
+    /// Send a query, and wait for the answer.
+    ///
+    /// Returns an error if the messenger was dropped, or dropped the request
+    /// without responding.
+    pub async fn request(&self, query: Q) -> Result<A, RecvError> {
+        self.0.request(query).await
+    }
+}
+
+impl<Q, A> Clone for Commander<Q, A> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Q, A> core::fmt::Debug for Commander<Q, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Commander").finish_non_exhaustive()
+    }
+}
+
+/// Handle for an actor to receive requests
+///
+/// Dropping the messenger fails waiting and future requests.
+pub struct Messenger<Q, A>(Channel<Request<Q, A>>);
+
+impl<Q, A> Messenger<Q, A> {
+    /// Receive the next request.
+    ///
+    /// Returns an error once every [`Commander`] is dropped and no requests
+    /// remain.
+    pub async fn recv(&self) -> Result<Request<Q, A>, RecvError> {
+        self.0.recv().await
+    }
+}
+
+impl<Q, A> Drop for Messenger<Q, A> {
+    fn drop(&mut self) {
+        // Dropping unhandled requests wakes their requesters with an error
+        drop(self.0.close_and_collect());
+    }
+}
+
+impl<Q, A> core::fmt::Debug for Messenger<Q, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Messenger").finish_non_exhaustive()
+    }
+}
+
+/// State that handles messages received by its [`Mailbox`], one at a time
+///
+/// ```rust
+/// use core::future::{ready, Ready};
+///
+/// use whisk::{
+///     actor::{spawn_actor, Actor},
+///     Oneshot, OneshotSender,
+/// };
+///
+/// enum Msg {
+///     Add(u32),
+///     Get(OneshotSender<u32>),
+/// }
+///
+/// struct Counter(u32);
+///
+/// impl Actor for Counter {
+///     type Msg = Msg;
+///     type Handle<'a> = Ready<()>;
+///
+///     fn handle(&mut self, msg: Msg) -> Ready<()> {
+///         match msg {
+///             Msg::Add(n) => self.0 += n,
+///             Msg::Get(reply) => reply.send(self.0).unwrap(),
+///         }
+///
+///         ready(())
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let counter = spawn_actor(Counter(0), |actor| {
+///         tokio::spawn(actor);
+///     });
+///     let (oneshot, reply) = Oneshot::new();
+///
+///     counter.send(Msg::Add(2)).await.unwrap();
+///     counter.send(Msg::Add(3)).await.unwrap();
+///     counter.send(Msg::Get(reply)).await.unwrap();
+///     assert_eq!(oneshot.recv().await, Ok(5));
+/// }
+/// ```
+pub trait Actor {
+    /// Type of message the actor handles
+    type Msg;
+
+    /// Future returned from [`Actor::handle()`]
+    ///
+    /// Handlers that need to await can use a boxed future.
+    type Handle<'a>: Future<Output = ()>
+    where
+        Self: 'a;
+
+    /// Handle a message.
+    fn handle(&mut self, msg: Self::Msg) -> Self::Handle<'_>;
+}
+
+/// Spawner for running actors, keeping whisk executor-agnostic
+///
+/// Implemented for functions taking a boxed future, such as a closure calling
+/// an async runtime's `spawn()`.
+pub trait Spawn {
+    /// Run `future` to completion in the background.
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>);
+}
+
+impl<F> Spawn for F
+where
+    F: Fn(Pin<Box<dyn Future<Output = ()> + Send>>),
+{
+    #[inline(always)]
+    fn spawn(&self, future: Pin<Box<dyn Future<Output = ()> + Send>>) {
+        self(future)
+    }
+}
+
+/// Queue of messages for an [`Actor`]
+///
+/// Messages are sent to the mailbox through its [`address()`](Self::address)
+/// handles, which can be cloned.  Enable the **`futures_core_3`** feature for
+/// `Mailbox` to implement [`Stream`](futures_core_3::Stream).
+pub struct Mailbox<A: Actor>(Receiver<A::Msg>);
+
+impl<A: Actor> Mailbox<A> {
+    /// Create a new, empty mailbox.
+    pub fn new() -> Self {
+        Self(Channel::new().into_receiver())
+    }
+
+    /// Get a new handle for sending messages to this mailbox.
+    pub fn address(&self) -> Sender<A::Msg> {
+        self.0.clone().into_inner().into_sender()
+    }
+
+    /// Receive the next message.
+    ///
+    /// Returns an error once every address is dropped and no messages
+    /// remain, or if the channel is closed.
+    pub async fn recv(&self) -> Result<A::Msg, RecvError> {
+        self.0.recv().await
+    }
+
+    /// Handle every message with `actor` until [`recv()`](Self::recv)
+    /// returns an error, then return the actor.
+    pub async fn run(self, mut actor: A) -> A {
+        while let Ok(msg) = self.0.recv().await {
+            actor.handle(msg).await;
+        }
+
+        actor
+    }
+}
+
+impl<A: Actor> Default for Mailbox<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Actor> core::fmt::Debug for Mailbox<A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Mailbox").finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "futures_core_3")]
+impl<A: Actor> futures_core_3::Stream for Mailbox<A> {
+    type Item = A::Msg;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> Poll<Option<A::Msg>> {
+        Pin::new(&mut self.get_mut().0).poll_next(cx)
+    }
+}
+
+/// Run `actor` on a new [`Mailbox`] with `spawner`, returning its address.
+///
+/// The actor stops once every address is dropped.
+pub fn spawn_actor<A>(actor: A, spawner: impl Spawn) -> Sender<A::Msg>
+where
+    A: Actor + Send + 'static,
+    A::Msg: Send,
+    for<'a> A::Handle<'a>: Send,
+{
+    let mailbox = Mailbox::new();
+    let address = mailbox.address();
+
+    spawner.spawn(Box::pin(async move {
+        mailbox.run(actor).await;
+    }));
+
+    address
+}
+
+/// Why a supervised actor stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// Every address was dropped, and the remaining messages were handled
+    Stopped,
+    /// The actor's task was dropped before stopping, such as by a panic in
+    /// [`Actor::handle()`]
+    Failed,
+}
+
+/// Which actors a [`Supervisor`] restarts when one fails
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Restart only the failed actor
+    #[default]
+    OneForOne,
+    /// Stop every other running actor, then restart them all
+    OneForAll,
+}
+
+/// Notification sent by a supervised actor's task when it ends
+struct ChildExit<M> {
+    index: usize,
+    exit: Exit,
+    /// True if stopped by the supervisor to be restarted
+    requested: bool,
+    mailbox: Channel<M>,
+}
+
+/// Sends the exit notification of a supervised actor, as a failure if
+/// dropped first, so that panics are reported
+struct Child<M> {
+    index: usize,
+    mailbox: Option<Channel<M>>,
+    exits: Channel<ChildExit<M>>,
+}
+
+impl<M> Child<M> {
+    /// Send the exit notification, returning the mailbox to the supervisor
+    fn exit(&mut self, exit: Exit, requested: bool) {
+        let Some(mailbox) = self.mailbox.take() else {
+            return;
+        };
+
+        // Has room for every child, and fails only if the supervisor is gone
+        let _ = self.exits.try_send(ChildExit {
+            index: self.index,
+            exit,
+            requested,
+            mailbox,
+        });
+    }
+}
+
+impl<M> Drop for Child<M> {
+    fn drop(&mut self) {
+        self.exit(Exit::Failed, false);
+    }
+}
+
+/// Runs a fixed set of actors, restarting them when they fail
+///
+/// Each actor's mailbox outlives restarts, so addresses stay valid, and
+/// messages sent while an actor is restarting wait for the new actor.
+/// Dropping the supervisor stops its actors once they finish their current
+/// message.  A panicking actor is only reported as failed if the panic
+/// unwinds, and the executor drops the panicked task.
+///
+/// ```rust
+/// use core::future::{ready, Ready};
+/// use std::sync::Mutex;
+///
+/// use whisk::{
+///     actor::{Actor, Exit, Strategy, Supervisor},
+///     Oneshot, OneshotSender, RecvError,
+/// };
+///
+/// struct Doubler;
+///
+/// impl Actor for Doubler {
+///     type Msg = (u32, OneshotSender<u32>);
+///     type Handle<'a> = Ready<()>;
+///
+///     fn handle(&mut self, (n, reply): Self::Msg) -> Ready<()> {
+///         assert_ne!(n, 0, "can't double zero");
+///         reply.send(n * 2).unwrap();
+///         ready(())
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let spawner = |actor| {
+///         tokio::spawn(actor);
+///     };
+///     let (supervisor, addresses) =
+///         Supervisor::new(1, Strategy::OneForOne, |_| Doubler, spawner);
+///     let exits = Mutex::new(Vec::new());
+///     let supervising = supervisor.run(|index, exit| {
+///         exits.lock().unwrap().push((index, exit));
+///     });
+///     let tasker = async move {
+///         let (oneshot, reply) = Oneshot::new();
+///
+///         addresses[0].send((0, reply)).await.unwrap();
+///         assert_eq!(oneshot.recv().await, Err(RecvError));
+///
+///         let (oneshot, reply) = Oneshot::new();
+///
+///         addresses[0].send((3, reply)).await.unwrap();
+///         assert_eq!(oneshot.recv().await, Ok(6));
+///     };
+///
+///     futures::future::join(supervising, tasker).await;
+///     assert_eq!(*exits.lock().unwrap(), [(0, Exit::Failed), (0, Exit::Stopped)]);
+/// }
+/// ```
+pub struct Supervisor<A: Actor, F, S> {
+    factory: F,
+    spawner: S,
+    strategy: Strategy,
+    /// Exit notifications from actor tasks
+    exits: Channel<ChildExit<A::Msg>>,
+    /// Mailboxes of actors that aren't running
+    mailboxes: Vec<Option<Channel<A::Msg>>>,
+    /// Senders for stopping each running actor
+    stops: Vec<Option<OneshotSender<()>>>,
+}
+
+impl<A, F, S> Supervisor<A, F, S>
+where
+    A: Actor + Send + 'static,
+    A::Msg: Send,
+    for<'a> A::Handle<'a>: Send,
+    F: Fn(usize) -> A,
+    S: Spawn,
+{
+    /// Spawn `actors` actors created by `factory` with `spawner`, returning
+    /// the supervisor and the address of each actor.
+    ///
+    /// # Panics
+    ///
+    /// If `actors` is zero.
+    pub fn new(
+        actors: usize,
+        strategy: Strategy,
+        factory: F,
+        spawner: S,
+    ) -> (Self, Vec<Sender<A::Msg>>) {
+        assert_ne!(actors, 0, "Supervisor must have at least one actor");
+
+        let mailboxes: Vec<_> = (0..actors).map(|_| Channel::new()).collect();
+        let addresses = mailboxes
+            .iter()
+            .map(|mailbox| mailbox.clone().into_sender())
+            .collect();
+        let mut supervisor = Self {
+            factory,
+            spawner,
+            strategy,
+            exits: Channel::with_capacity(actors),
+            mailboxes: mailboxes.into_iter().map(Some).collect(),
+            stops: (0..actors).map(|_| None).collect(),
+        };
+
+        for index in 0..actors {
+            supervisor.start(index);
+        }
+
+        (supervisor, addresses)
+    }
+
+    /// Supervise the actors until every one has stopped, calling `on_exit`
+    /// with the index of each actor that exits.
+    pub async fn run(mut self, mut on_exit: impl FnMut(usize, Exit)) {
+        let mut running = self.stops.len();
+        let mut restarting = Vec::new();
+
+        while running > 0 {
+            let Ok(child) = self.exits.recv().await else {
+                break;
+            };
+            let failed = !child.requested && child.exit == Exit::Failed;
+
+            running -= 1;
+            self.stops[child.index] = None;
+
+            if !child.requested {
+                on_exit(child.index, child.exit);
+            }
+
+            if child.requested || failed {
+                self.mailboxes[child.index] = Some(child.mailbox);
+                restarting.push(child.index);
+            }
+
+            if failed && self.strategy == Strategy::OneForAll {
+                for stop in self.stops.iter_mut().filter_map(Option::take) {
+                    let _ = stop.send(());
+                }
+            }
+
+            // With one for all, wait for every actor to stop before restarting
+            if self.strategy == Strategy::OneForAll && running > 0 {
+                continue;
+            }
+
+            for index in restarting.drain(..) {
+                self.start(index);
+                running += 1;
+            }
+        }
+    }
+
+    /// Spawn a new actor on the mailbox at `index`.
+    fn start(&mut self, index: usize) {
+        let mailbox = self.mailboxes[index].take().expect("actor running");
+        let (stopping, stop) = Oneshot::new();
+        let mut actor = (self.factory)(index);
+        let mut child = Child {
+            index,
+            mailbox: Some(mailbox),
+            exits: self.exits.clone(),
+        };
+
+        self.stops[index] = Some(stop);
+        self.spawner.spawn(Box::pin(async move {
+            let mut stopping = core::pin::pin!(stopping.recv());
+
+            loop {
+                let next = {
+                    let mailbox = child.mailbox.as_ref().expect("actor exited");
+                    let mut recv = mailbox.recv();
+
+                    core::future::poll_fn(|cx| {
+                        if stopping.as_mut().poll(cx).is_ready() {
+                            return Poll::Ready(None);
+                        }
+
+                        Pin::new(&mut recv).poll(cx).map(Some)
+                    })
+                    .await
+                };
+
+                match next {
+                    Some(Ok(msg)) => actor.handle(msg).await,
+                    Some(Err(_)) => return child.exit(Exit::Stopped, false),
+                    None => return child.exit(Exit::Stopped, true),
+                }
+            }
+        }));
+    }
+}
+
+impl<A: Actor, F, S> core::fmt::Debug for Supervisor<A, F, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Supervisor")
+            .field("strategy", &self.strategy)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{Channel, RecvError, SendError, TryRecvError, TrySendError};
+
+/// A view of a channel converting messages as they're received, and
+/// optionally as they're sent
+///
+/// Conversions run in the task receiving or sending, so there is no
+/// converter task to spawn.  Converting a `Mapped` back with
+/// [`into_inner()`](Mapped::into_inner) gives the underlying channel.
+///
+/// ```rust
+/// use whisk::{Channel, Mapped};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::with_capacity(2);
+///         let celsius = Mapped::new(channel.clone(), |c: f32| c as i32)
+///             .with_send(|c| c as f32);
+///
+///         channel.send(21.5).await.unwrap();
+///         celsius.send(30).await.unwrap();
+///         assert_eq!(celsius.recv().await, Ok(21));
+///         assert_eq!(channel.recv().await, Ok(30.0));
+///     });
+/// }
+/// ```
+pub struct Mapped<T, U> {
+    channel: Channel<T>,
+    /// Conversion from received messages
+    recv: fn(T) -> U,
+    /// Conversion to sent messages
+    send: Option<fn(U) -> T>,
+}
+
+impl<T, U> Mapped<T, U> {
+    /// Create a view of `channel`, receiving messages converted by `f`.
+    pub fn new(channel: Channel<T>, f: fn(T) -> U) -> Self {
+        Self {
+            channel,
+            recv: f,
+            send: None,
+        }
+    }
+
+    /// Allow sending, with messages converted by `f`.
+    pub fn with_send(mut self, f: fn(U) -> T) -> Self {
+        self.send = Some(f);
+        self
+    }
+
+    /// Receive a message, converted.
+    pub async fn recv(&self) -> Result<U, RecvError> {
+        self.channel.recv().await.map(self.recv)
+    }
+
+    /// Receive a message if one is available, converted, without waiting.
+    pub fn try_recv(&self) -> Result<U, TryRecvError> {
+        self.channel.try_recv().map(self.recv)
+    }
+
+    /// Convert a message, then send it.
+    ///
+    /// If the channel is closed, the converted message is returned in the
+    /// error.
+    ///
+    /// # Panics
+    ///
+    /// If created without [`with_send()`](Mapped::with_send).
+    pub async fn send(&self, message: U) -> Result<(), SendError<T>> {
+        self.channel.send(self.convert(message)).await
+    }
+
+    /// Convert a message, then send it if there is room, without waiting.
+    ///
+    /// # Panics
+    ///
+    /// If created without [`with_send()`](Mapped::with_send).
+    pub fn try_send(&self, message: U) -> Result<(), TrySendError<T>> {
+        self.channel.try_send(self.convert(message))
+    }
+
+    /// Convert back into the underlying channel handle.
+    pub fn into_inner(self) -> Channel<T> {
+        self.channel
+    }
+
+    /// Convert a message to send
+    fn convert(&self, message: U) -> T {
+        let f = self
+            .send
+            .expect("Mapped channel created without with_send()");
+
+        f(message)
+    }
+}
+
+impl<T, U> Clone for Mapped<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+            recv: self.recv,
+            send: self.send,
+        }
+    }
+}
+
+impl<T, U> core::fmt::Debug for Mapped<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Mapped").finish_non_exhaustive()
+    }
+}
+
+/// A view of a channel only receiving messages that satisfy a predicate
+///
+/// Other messages are left in the channel for other receivers, as with
+/// [`Channel::recv_if()`].
+///
+/// ```rust
+/// use whisk::{Channel, Filtered};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::with_capacity(3);
+///         let errors = Filtered::new(channel.clone(), |line: &&str| {
+///             line.starts_with("error")
+///         });
+///
+///         channel.send("info: started").await.unwrap();
+///         channel.send("error: disk full").await.unwrap();
+///         assert_eq!(errors.recv().await, Ok("error: disk full"));
+///         assert_eq!(channel.recv().await, Ok("info: started"));
+///     });
+/// }
+/// ```
+pub struct Filtered<T> {
+    channel: Channel<T>,
+    /// Predicate for messages to receive
+    f: fn(&T) -> bool,
+}
+
+impl<T> Filtered<T> {
+    /// Create a view of `channel`, receiving only messages that satisfy `f`.
+    pub fn new(channel: Channel<T>, f: fn(&T) -> bool) -> Self {
+        Self { channel, f }
+    }
+
+    /// Receive the first message that satisfies the predicate.
+    pub async fn recv(&self) -> Result<T, RecvError> {
+        self.channel.recv_if(self.f).await
+    }
+
+    /// Send a message, whether or not it satisfies the predicate.
+    pub async fn send(&self, message: T) -> Result<(), SendError<T>> {
+        self.channel.send(message).await
+    }
+
+    /// Convert back into the underlying channel handle.
+    pub fn into_inner(self) -> Channel<T> {
+        self.channel
+    }
+}
+
+impl<T> Clone for Filtered<T> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+            f: self.f,
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Filtered<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Filtered").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,37 @@
+//! Atomics and reference counting, from `portable-atomic` when the
+//! **`portable_atomic`** feature is enabled
+//!
+//! Reference counting only comes from `portable-atomic-util` on targets
+//! without native compare-and-swap, where `alloc::sync` doesn't exist, so
+//! enabling the feature elsewhere doesn't change `Arc` in public APIs.
+
+#[cfg(any(not(feature = "portable_atomic"), target_has_atomic = "ptr"))]
+pub(crate) use alloc::sync::{Arc, Weak};
+#[cfg(not(feature = "portable_atomic"))]
+pub(crate) use core::sync::atomic::{
+    AtomicBool, AtomicPtr, AtomicU8, AtomicUsize, Ordering,
+};
+
+#[cfg(feature = "portable_atomic")]
+pub(crate) use portable_atomic::{
+    AtomicBool, AtomicPtr, AtomicU8, AtomicUsize, Ordering,
+};
+#[cfg(all(feature = "portable_atomic", not(target_has_atomic = "ptr")))]
+pub(crate) use portable_atomic_util::{Arc, Weak};
+
+/// Atomics of the mutex and wake lists, from `loom` when built with
+/// `--cfg loom`, so `tests/loom.rs` can check every interleaving of them
+pub(crate) mod checked {
+    #[cfg(not(loom))]
+    pub(crate) use core::sync::atomic::fence;
+
+    #[cfg(all(loom, not(feature = "fixed_wakers")))]
+    pub(crate) use loom::sync::atomic::AtomicPtr;
+    #[cfg(loom)]
+    pub(crate) use loom::sync::atomic::{fence, AtomicBool, AtomicUsize};
+
+    #[cfg(all(not(loom), not(feature = "fixed_wakers")))]
+    pub(crate) use super::AtomicPtr;
+    #[cfg(not(loom))]
+    pub(crate) use super::{AtomicBool, AtomicUsize};
+}
@@ -0,0 +1,114 @@
+//! Strategies for waiting on a contended channel lock
+//!
+//! Channel locks are only held briefly, so by default a thread waiting on one
+//! spins.  On oversubscribed systems, where the thread holding the lock may
+//! have been preempted, yielding to the OS scheduler lets it finish sooner.
+//! The strategy is set once for the whole process, and doesn't apply with the
+//! **pi_lock** or **std_lock** features, which block instead.
+//!
+//! ```rust
+//! use whisk::{backoff, Channel};
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     backoff::set_backoff::<backoff::Exponential>();
+//!
+//!     pasts::Executor::default().block_on(async {
+//!         let channel = Channel::new();
+//!         let sending = channel.send(5);
+//!         let (sent, received) =
+//!             futures::future::join(sending, channel.recv()).await;
+//!
+//!         sent.unwrap();
+//!         assert_eq!(received, Ok(5));
+//!     });
+//! }
+//! ```
+
+use core::{hint, ptr};
+
+use crate::atomic::{AtomicPtr, Ordering::Relaxed};
+
+/// The backoff function set for the process, or null for the default
+static BACKOFF: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+/// A strategy for waiting between attempts to acquire a channel lock
+pub trait Backoff {
+    /// Wait before trying again, after `attempt` previous failed attempts.
+    fn backoff(attempt: u32);
+}
+
+/// Spin in a tight loop (the default)
+///
+/// Best when every thread has its own core, such as on embedded targets.
+#[derive(Copy, Clone, Debug)]
+pub struct Spin;
+
+impl Backoff for Spin {
+    fn backoff(_attempt: u32) {
+        hint::spin_loop();
+    }
+}
+
+/// Spin for exponentially longer after each failed attempt, up to 64 spins
+///
+/// Reduces traffic on the lock's cache line when many cores contend.
+#[derive(Copy, Clone, Debug)]
+pub struct Exponential;
+
+impl Backoff for Exponential {
+    fn backoff(attempt: u32) {
+        for _ in 0..1u32 << attempt.min(6) {
+            hint::spin_loop();
+        }
+    }
+}
+
+/// Spin for a few attempts, then yield to the OS scheduler
+///
+/// Best on oversubscribed systems, where the lock holder may be preempted.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub struct SpinThenYield;
+
+#[cfg(feature = "std")]
+impl Backoff for SpinThenYield {
+    fn backoff(attempt: u32) {
+        if attempt < 8 {
+            Exponential::backoff(attempt);
+        } else {
+            std::thread::yield_now();
+        }
+    }
+}
+
+/// Set the backoff strategy for every channel in the process.
+pub fn set_backoff<B: Backoff>() {
+    let backoff: fn(u32) = B::backoff;
+
+    BACKOFF.store(backoff as *mut (), Relaxed);
+}
+
+/// Wait before trying to acquire a lock again, with the strategy set for the
+/// process
+#[cfg(not(any(
+    all(feature = "pi_lock", target_os = "linux"),
+    feature = "std_lock"
+)))]
+pub(crate) fn backoff(attempt: u32) {
+    let backoff = BACKOFF.load(Relaxed);
+
+    if backoff.is_null() {
+        // Let a preempted holder run, rather than spinning on it
+        #[cfg(feature = "pi_lock")]
+        SpinThenYield::backoff(attempt);
+        #[cfg(not(feature = "pi_lock"))]
+        Spin::backoff(attempt);
+        return;
+    }
+
+    // Safe because only `fn(u32)` pointers are stored
+    let backoff: fn(u32) = unsafe { core::mem::transmute(backoff) };
+
+    backoff(attempt);
+}
@@ -0,0 +1,54 @@
+use alloc::sync::Arc;
+use std::sync::RwLock;
+
+/// Callback invoked on backpressure events
+type Callback = Arc<dyn Fn(BackpressureEvent) + Send + Sync>;
+
+/// A sender parking on, or unparking from, a full channel
+///
+/// Passed to the callback installed with
+/// [`Channel::on_backpressure()`](crate::Channel::on_backpressure).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackpressureEvent {
+    /// A sender started waiting for a free slot
+    Parked {
+        /// Number of messages in transit
+        occupancy: usize,
+    },
+    /// A waiting sender stopped waiting for a free slot
+    Unparked {
+        /// Number of messages in transit
+        occupancy: usize,
+    },
+}
+
+/// Installed backpressure callback
+pub(crate) struct Hook(RwLock<Option<Callback>>);
+
+impl Hook {
+    /// Create a hook without a callback
+    pub(crate) const fn new() -> Self {
+        Self(RwLock::new(None))
+    }
+
+    /// Replace the callback
+    pub(crate) fn set(&self, callback: Option<Callback>) {
+        let old = core::mem::replace(
+            &mut *self.0.write().unwrap_or_else(|e| e.into_inner()),
+            callback,
+        );
+
+        // Drop the old callback outside of the lock
+        drop(old);
+    }
+
+    /// Invoke the callback, if set
+    pub(crate) fn fire(&self, event: BackpressureEvent) {
+        let callback = self.0.read().unwrap_or_else(|e| e.into_inner()).clone();
+
+        // Call outside of the lock, so the callback can replace itself
+        if let Some(callback) = callback {
+            callback(event);
+        }
+    }
+}
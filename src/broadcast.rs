@@ -0,0 +1,292 @@
+//! Broadcast channels for fanning out messages to every subscriber
+//!
+//! Every message sent on a [`Channel`] is received by each [`Subscriber`]
+//! that existed when it was sent, in order.  Each subscriber has its own
+//! cursor, and messages are buffered until all of their subscribers have
+//! received them.
+//!
+//! **A channel created with [`Channel::new()`] is unbounded**, so one
+//! subscriber that never receives makes the buffer grow without limit.  A
+//! channel created with [`Channel::with_capacity()`] drops its oldest message
+//! instead, and subscribers that fall behind receive
+//! [`BroadcastRecvError::Lagged`] before skipping ahead.
+//!
+//! ```rust
+//! use whisk::{broadcast::Channel, BroadcastRecvError::Closed};
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let channel = Channel::new();
+//!         let mut first = channel.subscribe();
+//!
+//!         channel.send("resized");
+//!
+//!         // Late subscribers only receive later messages
+//!         let mut second = channel.subscribe();
+//!
+//!         channel.send("focused");
+//!         drop(channel);
+//!
+//!         assert_eq!(first.recv().await, Ok("resized"));
+//!         assert_eq!(first.recv().await, Ok("focused"));
+//!         assert_eq!(first.recv().await, Err(Closed));
+//!         assert_eq!(second.recv().await, Ok("focused"));
+//!         assert_eq!(second.recv().await, Err(Closed));
+//!     });
+//! }
+//! ```
+
+use alloc::{collections::VecDeque, vec::Vec};
+use core::{future, task::Poll};
+
+use crate::{
+    atomic::Arc, mutex::Mutex, wake_list::WakeHandle, BroadcastRecvError,
+};
+
+/// Messages protected by the mutex
+struct Locked<T> {
+    /// Buffered messages, with the number of subscribers yet to receive them
+    messages: VecDeque<(T, usize)>,
+    /// Sequence number of the oldest buffered message
+    head: u64,
+    /// Maximum number of buffered messages
+    capacity: usize,
+    /// Number of subscribers
+    subscribers: usize,
+    /// Number of sending channel handles
+    senders: usize,
+}
+
+impl<T: Clone> Locked<T> {
+    /// Take the message at a subscriber's cursor, advancing it
+    fn take(&mut self, cursor: &mut u64) -> Option<T> {
+        let index = (*cursor - self.head) as usize;
+        let (message, readers) = self.messages.get_mut(index)?;
+
+        *cursor += 1;
+        *readers -= 1;
+
+        if *readers != 0 {
+            return Some(message.clone());
+        }
+
+        // The last subscriber to receive a message always receives the
+        // oldest, so it can be moved out instead of cloned
+        self.head += 1;
+        self.messages.pop_front().map(|(message, _)| message)
+    }
+}
+
+/// The sending half of a broadcast channel
+///
+/// Sending never waits, and the channel closes once every clone of it has
+/// been dropped.
+pub struct Channel<T>(Arc<Mutex<Locked<T>>>);
+
+impl<T> Channel<T> {
+    /// Create a new unbounded broadcast channel with no subscribers.
+    ///
+    /// Messages are buffered until every subscriber has received them, so a
+    /// subscriber that never receives makes the buffer grow without limit.
+    /// Use [`Channel::with_capacity()`] unless every subscriber keeps up.
+    pub fn new() -> Self {
+        Self::with_capacity(usize::MAX)
+    }
+
+    /// Create a new broadcast channel with no subscribers, buffering up to
+    /// `capacity` messages.
+    ///
+    /// Sending still never waits: once full, the oldest message is dropped,
+    /// and subscribers that hadn't received it get
+    /// [`BroadcastRecvError::Lagged`] with the number of messages they
+    /// missed, then continue from the oldest message still buffered.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    ///
+    /// ```rust
+    /// use whisk::{broadcast::Channel, BroadcastRecvError::Lagged};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::with_capacity(2);
+    ///         let mut slow = channel.subscribe();
+    ///
+    ///         for event in 0..5 {
+    ///             channel.send(event);
+    ///         }
+    ///
+    ///         assert_eq!(slow.recv().await, Err(Lagged(3)));
+    ///         assert_eq!(slow.recv().await, Ok(3));
+    ///         assert_eq!(slow.recv().await, Ok(4));
+    ///     });
+    /// }
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        assert_ne!(capacity, 0, "Capacity must be at least one");
+
+        Self(Arc::new(Mutex::new(Locked {
+            messages: VecDeque::new(),
+            head: 0,
+            capacity,
+            subscribers: 0,
+            senders: 1,
+        })))
+    }
+
+    /// Create a new subscriber, receiving messages sent from now on.
+    pub fn subscribe(&self) -> Subscriber<T> {
+        let cursor = self.0.with(|locked| {
+            locked.subscribers += 1;
+            locked.head + locked.messages.len() as u64
+        });
+
+        Subscriber {
+            shared: Arc::clone(&self.0),
+            cursor,
+        }
+    }
+
+    /// Get the number of subscribers to this channel.
+    pub fn subscribers(&self) -> usize {
+        self.0.with(|locked| locked.subscribers)
+    }
+
+    /// Send a message to every current subscriber.
+    ///
+    /// Returns the number of subscribers that will receive the message.  If
+    /// there are none, the message is dropped.  If the channel is full, the
+    /// oldest message is dropped to make room.
+    pub fn send(&self, message: T) -> usize {
+        let (subscribers, dropped) = self.0.with(|locked| {
+            if locked.subscribers == 0 {
+                return (0, Some(message));
+            }
+
+            let dropped = if locked.messages.len() == locked.capacity {
+                locked.head += 1;
+                locked.messages.pop_front().map(|(message, _)| message)
+            } else {
+                None
+            };
+
+            locked.messages.push_back((message, locked.subscribers));
+            (locked.subscribers, dropped)
+        });
+
+        // Drop messages outside of the lock
+        drop(dropped);
+        self.0.wake_all_recv();
+        subscribers
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        self.0.with(|locked| locked.senders += 1);
+
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Default for Channel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Channel<T> {
+    fn drop(&mut self) {
+        let closed = self.0.with(|locked| {
+            locked.senders -= 1;
+            locked.senders == 0
+        });
+
+        if closed {
+            self.0.wake_all_recv();
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Channel<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a broadcast channel, with its own cursor
+pub struct Subscriber<T> {
+    shared: Arc<Mutex<Locked<T>>>,
+    /// Sequence number of the next message to receive
+    cursor: u64,
+}
+
+impl<T: Clone> Subscriber<T> {
+    /// Receive the next message sent to this subscriber.
+    ///
+    /// Returns [`BroadcastRecvError::Closed`] once every [`Channel`] has been
+    /// dropped and all messages have been received, or
+    /// [`BroadcastRecvError::Lagged`] if messages were dropped before this
+    /// subscriber received them.
+    pub async fn recv(&mut self) -> Result<T, BroadcastRecvError> {
+        let mut wh = WakeHandle::new();
+        let Self { shared, cursor } = self;
+
+        future::poll_fn(|cx| {
+            shared.poll_recv(cx, &mut wh, |locked| {
+                if *cursor < locked.head {
+                    let missed = locked.head - *cursor;
+
+                    // Skip ahead to the oldest message still buffered
+                    *cursor = locked.head;
+                    Poll::Ready(Err(BroadcastRecvError::Lagged(missed)))
+                } else if let Some(message) = locked.take(cursor) {
+                    Poll::Ready(Ok(message))
+                } else if locked.senders == 0 {
+                    Poll::Ready(Err(BroadcastRecvError::Closed))
+                } else {
+                    Poll::Pending
+                }
+            })
+        })
+        .await
+    }
+}
+
+impl<T> Drop for Subscriber<T> {
+    fn drop(&mut self) {
+        let cursor = self.cursor;
+
+        // Release messages this subscriber would have received
+        let released = self.shared.with(|locked| {
+            // Messages dropped while lagging were already released
+            let start = cursor.saturating_sub(locked.head) as usize;
+
+            locked.subscribers -= 1;
+
+            for (_, readers) in locked.messages.iter_mut().skip(start) {
+                *readers -= 1;
+            }
+
+            let read = locked.messages.iter().position(|(_, r)| *r != 0);
+            let read = read.unwrap_or(locked.messages.len());
+
+            locked.head += read as u64;
+            locked.messages.drain(..read).collect::<Vec<_>>()
+        });
+
+        // Drop messages outside of the lock
+        drop(released);
+    }
+}
+
+impl<T> core::fmt::Debug for Subscriber<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Subscriber")
+            .field("cursor", &self.cursor)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,160 @@
+//! Topic-based publish / subscribe for in-process events
+//!
+//! Publishers send to a topic on a [`Bus`], and every subscriber to that
+//! topic receives its own copy on its own channel.  Messages published to a
+//! topic without subscribers are dropped, and dropping a subscriber's
+//! [`Receiver`] unsubscribes it.
+//!
+//! ```rust
+//! use whisk::{bus::Bus, RecvError};
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+//! enum Topic {
+//!     Input,
+//!     Network,
+//! }
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let bus = Bus::with_capacity(4);
+//!         let logger = bus.subscribe(Topic::Input);
+//!         let ui = bus.subscribe(Topic::Input);
+//!
+//!         assert_eq!(bus.publish(&Topic::Input, "click").await, 2);
+//!         assert_eq!(bus.publish(&Topic::Network, "connected").await, 0);
+//!         assert_eq!(logger.recv().await, Ok("click"));
+//!         assert_eq!(ui.recv().await, Ok("click"));
+//!
+//!         drop(ui);
+//!         assert_eq!(bus.publish(&Topic::Input, "scroll").await, 1);
+//!         assert_eq!(logger.recv().await, Ok("scroll"));
+//!
+//!         drop(bus);
+//!         assert_eq!(logger.recv().await, Err(RecvError));
+//!     });
+//! }
+//! ```
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::{atomic::Arc, mutex::Mutex, Channel, Receiver};
+
+/// Subscriptions protected by the mutex
+struct Locked<K, T> {
+    /// Channel of each subscriber, by topic
+    topics: BTreeMap<K, Vec<Channel<T>>>,
+    /// Capacity of each subscriber's channel
+    capacity: usize,
+}
+
+/// Return true if the subscriber's receiver has been dropped
+fn unsubscribed<T>(channel: &Channel<T>) -> bool {
+    let Channel(queue, ..) = channel;
+
+    queue
+        .data
+        .with(|locked| locked.handles + locked.senders + locked.receivers == 1)
+}
+
+/// An event bus, delivering messages published to a topic to each of its
+/// subscribers
+///
+/// Clones share the same subscriptions, and once every clone is dropped,
+/// subscribers receive [`RecvError`](crate::RecvError) after the messages
+/// already published.
+pub struct Bus<K, T>(Arc<Mutex<Locked<K, T>>>);
+
+impl<K: Ord, T> Bus<K, T> {
+    /// Create a new bus with no subscribers, giving each subscriber a channel
+    /// with a capacity of one message.
+    pub fn new() -> Self {
+        Self::with_capacity(1)
+    }
+
+    /// Create a new bus with no subscribers, giving each subscriber a channel
+    /// that can hold `capacity` messages.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(Locked {
+            topics: BTreeMap::new(),
+            capacity,
+        })))
+    }
+
+    /// Subscribe to `topic`, receiving messages published from now on.
+    pub fn subscribe(&self, topic: K) -> Receiver<T> {
+        let channel = self.0.with(|locked| {
+            let channel = Channel::with_capacity(locked.capacity);
+
+            locked
+                .topics
+                .entry(topic)
+                .or_default()
+                .push(channel.clone());
+            channel
+        });
+
+        channel.into_receiver()
+    }
+
+    /// Get the number of subscribers to `topic`.
+    pub fn subscribers(&self, topic: &K) -> usize {
+        self.0.with(|locked| {
+            locked.topics.get(topic).map_or(0, |channels| {
+                channels.iter().filter(|c| !unsubscribed(c)).count()
+            })
+        })
+    }
+
+    /// Publish a message to every subscriber of `topic`, waiting for room in
+    /// each subscriber's channel.
+    ///
+    /// Returns the number of subscribers the message was delivered to.  If
+    /// there are none, the message is dropped.
+    pub async fn publish(&self, topic: &K, message: T) -> usize
+    where
+        T: Clone,
+    {
+        let channels = self.0.with(|locked| {
+            let Some(channels) = locked.topics.get_mut(topic) else {
+                return Vec::new();
+            };
+
+            channels.retain(|channel| !unsubscribed(channel));
+
+            if channels.is_empty() {
+                locked.topics.remove(topic);
+                return Vec::new();
+            }
+
+            channels.clone()
+        });
+        let mut delivered = 0;
+
+        for channel in channels {
+            if channel.send(message.clone()).await.is_ok() {
+                delivered += 1;
+            }
+        }
+
+        delivered
+    }
+}
+
+impl<K, T> Clone for Bus<K, T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<K: Ord, T> Default for Bus<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> core::fmt::Debug for Bus<K, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Bus").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,164 @@
+use alloc::vec::Vec;
+use core::{
+    future, mem,
+    task::{Context, Poll},
+};
+
+use crate::{
+    atomic::{Arc, AtomicBool, Ordering::SeqCst, Weak},
+    mutex::Mutex,
+    wake_list::{WakeHandle, WakeList},
+};
+
+struct Shared {
+    /// True once cancelled
+    cancelled: AtomicBool,
+    /// List of waiting tasks
+    waiters: WakeList,
+    /// Child tokens to cancel along with this one
+    children: Mutex<Vec<Weak<Shared>>>,
+}
+
+impl Shared {
+    /// Cancel this token and its children, returning false if it was
+    /// already cancelled
+    fn cancel(&self) -> bool {
+        if self.cancelled.swap(true, SeqCst) {
+            return false;
+        }
+
+        self.waiters.wake_all();
+
+        for child in self.children.with(mem::take) {
+            if let Some(child) = child.upgrade() {
+                child.cancel();
+            }
+        }
+
+        true
+    }
+}
+
+/// A cancellation signal shared between tasks, which can have child tokens
+///
+/// Cancelling a token wakes every task waiting on it, and cancels its
+/// children, but not its parent.  Clones share the same signal.
+///
+/// ```rust
+/// use whisk::{CancelToken, Channel};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let token = CancelToken::new();
+///         let child = token.child();
+///         let channel = Channel::<u32>::new();
+///         let cancelling = async { token.cancel() };
+///         let (received, _) = futures::join!(
+///             channel.recv_until_cancelled(&child),
+///             cancelling,
+///         );
+///
+///         assert_eq!(received, None);
+///         assert!(child.is_cancelled());
+///
+///         // Cancelling a child doesn't cancel its parent
+///         let token = CancelToken::new();
+///
+///         token.child().cancel();
+///         assert!(!token.is_cancelled());
+///     });
+/// }
+/// ```
+pub struct CancelToken(Arc<Shared>);
+
+impl CancelToken {
+    /// Create a new token that hasn't been cancelled.
+    pub fn new() -> Self {
+        Self(Arc::new(Shared {
+            cancelled: AtomicBool::new(false),
+            waiters: WakeList::new(),
+            children: Mutex::new(Vec::new()),
+        }))
+    }
+
+    /// Create a child token, cancelled when this token is.
+    ///
+    /// The child is already cancelled if this token is.
+    pub fn child(&self) -> Self {
+        let child = Self::new();
+
+        self.0.children.with(|children| {
+            // Checked under the lock, so a concurrent cancel sees the child
+            if self.is_cancelled() {
+                child.0.cancelled.store(true, SeqCst);
+                return;
+            }
+
+            children.retain(|child| child.strong_count() > 0);
+            children.push(Arc::downgrade(&child.0));
+        });
+
+        child
+    }
+
+    /// Cancel this token and its children, waking every waiting task.
+    ///
+    /// Returns false if it was already cancelled.
+    pub fn cancel(&self) -> bool {
+        self.0.cancel()
+    }
+
+    /// Return true if this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(SeqCst)
+    }
+
+    /// Wait until this token is cancelled.
+    pub async fn cancelled(&self) {
+        let mut wh = WakeHandle::new();
+
+        future::poll_fn(|cx| self.poll_cancelled(cx, &mut wh)).await
+    }
+
+    /// Poll for cancellation, registering the waker of `cx` if not yet
+    /// cancelled
+    pub(crate) fn poll_cancelled(
+        &self,
+        cx: &mut Context<'_>,
+        wh: &mut WakeHandle,
+    ) -> Poll<()> {
+        if self.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        wh.register(&self.0.waiters, cx.waker());
+
+        // Check again in case of cancelling before registering
+        if self.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Clone for CancelToken {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish_non_exhaustive()
+    }
+}
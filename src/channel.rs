@@ -1,33 +1,230 @@
-use alloc::sync::Arc;
 use core::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
 };
 
-use crate::{wake_list::WakeHandle, Queue};
+use crate::{
+    actor::Request, atomic::Arc, queue::Role, wake_list::WakeHandle,
+    CancelToken, Oneshot, OneshotSender, OwnedPermit, Permit, Queue, Receiver,
+    Recv, RecvError, Reservation, Send, SendError, SendOnce, Sender,
+};
 
 /// An MPMC channel with both send and receive capabilities
 ///
 /// Enable the **`futures_core_3`** feature for `Channel` to implement
 /// [`Stream`](futures_core_3::Stream) (generic `T` must be `Option<Item>`).
+/// Once the stream yields `None`, that handle stays finished, as reported by
+/// [`FusedStream`](futures_core_3::FusedStream).
+///
+/// Enable the **`futures_sink_3`** feature for `Channel` to implement
+/// [`Sink`](futures_sink_3::Sink).
 ///
 /// Enable the **`event_iterator`** feature for `Channel` to implement
 /// [`EventIterator`](event_iterator::EventIterator).
-pub struct Channel<T = (), U: ?Sized = ()>(Arc<Queue<T, U>>, WakeHandle);
+///
+/// Once every other handle to the channel has been dropped, the remaining
+/// handle is disconnected, and receiving drains the messages in transit and
+/// then returns [`RecvError`].  Cloning the remaining handle reconnects it, and
+/// handles converted to an [`Arc`] keep the channel connected.
+///
+/// ```rust
+/// use whisk::{Channel, RecvError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let sender = channel.clone();
+///         let sending = async move {
+///             sender.send(1).await.unwrap();
+///         };
+///         let receiving = async {
+///             assert_eq!(channel.recv().await, Ok(1));
+///             assert_eq!(channel.recv().await, Err(RecvError));
+///         };
+///
+///         futures::join!(sending, receiving);
+///     });
+/// }
+/// ```
+pub struct Channel<T = (), U: ?Sized = ()>(
+    pub(crate) Arc<Queue<T, U>>,
+    pub(crate) WakeHandle,
+    /// True once this handle has ended its stream
+    #[cfg_attr(not(feature = "futures_core_3"), allow(dead_code))]
+    pub(crate) bool,
+    /// Registration for sending, separate from receiving
+    pub(crate) WakeHandle,
+    /// True while [`Channel::poll_send()`] is waiting for a free slot
+    pub(crate) bool,
+    /// What this handle can do, tracked for disconnection
+    pub(crate) Role,
+    /// True while holding a slot reserved by `Sink::poll_ready()`
+    pub(crate) bool,
+);
 
 impl<T, U: ?Sized> Drop for Channel<T, U> {
     fn drop(&mut self) {
-        // Drop to avoid use after free
-        self.1 = WakeHandle::new();
+        // Unregister to avoid use after free, passing on unhandled wakeups
+        self.0.data.cancel_recv(&mut self.1);
+        self.0.data.cancel_send(&mut self.3);
+
+        // Dropped while `poll_send()` was waiting for a free slot
+        #[cfg(feature = "backpressure")]
+        if self.4 {
+            let occupancy = self.0.data.with(|locked| locked.data.len());
+
+            self.0.parked_changed(true, false, occupancy);
+        }
+
+        // Wake handles that this one leaves disconnected
+        let (role, reserved) = (self.5, self.6);
+        let disconnected = self.0.data.with(|locked| {
+            // Release a slot reserved by `Sink::poll_ready()` but never filled
+            locked.reserved -= usize::from(reserved);
+            locked.remove_handle(role)
+        });
+
+        self.wake_disconnected(disconnected);
+
+        if reserved {
+            self.0.data.wake_send();
+        }
     }
 }
 
+/// Create a new channel, split into a handle that can only send and a handle
+/// that can only receive.
+///
+/// This is shorthand for [`Channel::new()`] followed by [`Channel::split()`].
+/// Either handle can be cloned for more senders or receivers.  Once every
+/// [`Sender`] is dropped, receivers see the channel as disconnected, and once
+/// every [`Receiver`] is dropped, sending fails.
+///
+/// ```rust
+/// use whisk::RecvError;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let (sender, receiver) = whisk::channel();
+///         let sending = async move {
+///             sender.send("hello").await.unwrap();
+///         };
+///         let (_, received) =
+///             futures::future::join(sending, receiver.recv()).await;
+///
+///         assert_eq!(received, Ok("hello"));
+///         assert_eq!(receiver.recv().await, Err(RecvError));
+///     });
+/// }
+/// ```
+///
+/// Disconnection counts each direction separately, however many handles are
+/// cloned on the other side.
+///
+/// ```rust
+/// use whisk::{RecvError, TrySendError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let (sender, receiver) = whisk::channel::<u32>();
+///         let other_receiver = receiver.clone();
+///         let receiving = async {
+///             assert_eq!(receiver.recv().await, Err(RecvError));
+///         };
+///
+///         futures::join!(receiving, async move { drop(sender) });
+///         assert_eq!(other_receiver.recv().await, Err(RecvError));
+///
+///         let (sender, receiver) = whisk::channel();
+///         let other_sender = sender.clone();
+///
+///         drop(receiver);
+///         assert_eq!(sender.try_send(1), Err(TrySendError::Closed(1)));
+///         assert!(other_sender.send(2).await.is_err());
+///     });
+/// }
+/// ```
+#[inline(always)]
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    Channel::new().split()
+}
+
 impl<T> Channel<T> {
     /// Create a new channel.
     #[inline(always)]
     pub fn new() -> Self {
-        Self(Arc::new(Queue::new()), WakeHandle::new())
+        Self::from(Arc::new(Queue::new()))
+    }
+
+    /// Create a new channel that can hold `capacity` messages in transit, so
+    /// senders can get ahead of receivers.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::with_capacity(4);
+    ///
+    ///         // Doesn't wait for a receiver
+    ///         for i in 0..4 {
+    ///             channel.send(i).await.unwrap();
+    ///         }
+    ///
+    ///         for i in 0..4 {
+    ///             assert_eq!(channel.recv().await, Ok(i));
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from(Arc::new(Queue::with_capacity(capacity)))
+    }
+
+    /// Create a new channel that can hold `capacity` messages in transit,
+    /// with `policy` deciding what sending does when full.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    #[inline(always)]
+    pub fn with_policy(capacity: usize, policy: crate::OverflowPolicy) -> Self {
+        Self::from(Arc::new(Queue::with_policy(capacity, policy)))
+    }
+
+    /// Create a new channel that only holds the latest message.
+    ///
+    /// Sending never waits, and overwrites the message in transit if it
+    /// hasn't been received yet, so a slow receiver never stalls a publisher.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let state = Channel::latest();
+    ///
+    ///         state.send("loading").await.unwrap();
+    ///         state.send("ready").await.unwrap();
+    ///         assert_eq!(state.replace("done"), Ok(Some("ready")));
+    ///         assert_eq!(state.recv().await, Ok("done"));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn latest() -> Self {
+        Self::from(Arc::new(Queue::latest()))
     }
 }
 
@@ -42,20 +239,1368 @@ impl<T, U> Channel<T, U> {
 impl<T, U: ?Sized> Channel<T, U> {
     /// Send a message on this channel.
     #[inline(always)]
-    pub async fn send(&self, message: T) {
-        self.0.send(message).await
+    pub fn send(&self, message: T) -> Send<'_, T, U> {
+        self.0.send(message)
     }
 
     /// Receive a message from this channel.
     #[inline(always)]
-    pub async fn recv(&self) -> T {
-        self.0.recv().await
+    pub fn recv(&self) -> Recv<'_, T, U> {
+        self.0.recv()
+    }
+
+    /// Send every message from `messages`, pushing as many as fit under each
+    /// lock, and waking receivers once per batch.
+    ///
+    /// The iterator is advanced while the channel is locked, so it should be
+    /// cheap.  If the channel is closed, the unsent message is returned in a
+    /// [`SendError`], and the rest of the iterator is dropped.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::with_capacity(4);
+    ///         let sending = async {
+    ///             channel.send_all(0..10).await.unwrap();
+    ///             channel.seal();
+    ///         };
+    ///         let receiving = async {
+    ///             let mut received = Vec::new();
+    ///
+    ///             while channel.recv_many(&mut received, 4).await != 0 {}
+    ///
+    ///             received
+    ///         };
+    ///         let ((), received) = futures::join!(sending, receiving);
+    ///
+    ///         assert_eq!(received, (0..10).collect::<Vec<_>>());
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn send_all(
+        &self,
+        messages: impl IntoIterator<Item = T>,
+    ) -> Result<(), SendError<T>> {
+        self.0.send_all(messages).await
+    }
+
+    /// Wait for at least one message, then move up to `limit` messages into
+    /// `buffer` under a single lock.
+    ///
+    /// Returns the number of messages received, which is zero only if
+    /// `limit` is zero or the channel is closed.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::with_capacity(8);
+    ///         let mut buffer = Vec::new();
+    ///
+    ///         for i in 0..5 {
+    ///             channel.send(i).await.unwrap();
+    ///         }
+    ///
+    ///         assert_eq!(channel.recv_many(&mut buffer, 3).await, 3);
+    ///         assert_eq!(channel.recv_many(&mut buffer, 3).await, 2);
+    ///         assert_eq!(buffer, [0, 1, 2, 3, 4]);
+    ///
+    ///         channel.close();
+    ///         assert_eq!(channel.recv_many(&mut buffer, 3).await, 0);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn recv_many(
+        &self,
+        buffer: &mut alloc::vec::Vec<T>,
+        limit: usize,
+    ) -> usize {
+        self.0.recv_many(buffer, limit).await
+    }
+
+    /// Wait for at least one message, then receive up to `max` messages in
+    /// a chunk, under a single lock.
+    ///
+    /// Useful for processing messages in batches, with one wakeup per batch
+    /// rather than per message.  Returns [`RecvError`] if closed, or sealed
+    /// and drained.  The chunk is only empty if `max` is zero.
+    ///
+    /// ```rust
+    /// use whisk::{Channel, RecvError};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::with_capacity(8);
+    ///
+    ///         for i in 0..5 {
+    ///             channel.send(i).await.unwrap();
+    ///         }
+    ///         channel.seal();
+    ///
+    ///         assert_eq!(channel.recv_chunk(3).await, Ok(vec![0, 1, 2]));
+    ///         assert_eq!(channel.recv_chunk(3).await, Ok(vec![3, 4]));
+    ///         assert_eq!(channel.recv_chunk(3).await, Err(RecvError));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn recv_chunk(
+        &self,
+        max: usize,
+    ) -> Result<alloc::vec::Vec<T>, RecvError> {
+        self.0.recv_chunk(max).await
+    }
+
+    /// Wait for room, then send a default message that `f` fills in place in
+    /// the channel's buffer, avoiding a move of a large message through the
+    /// stack.
+    ///
+    /// The channel is locked while `f` runs, so it should be quick.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::<Vec<u8>>::new();
+    ///
+    ///         channel.send_with(|frame| frame.push(42)).await.unwrap();
+    ///         assert_eq!(channel.recv_with(|frame| frame[0]).await, Ok(42));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn send_with(
+        &self,
+        f: impl FnOnce(&mut T),
+    ) -> Result<(), SendError<()>>
+    where
+        T: Default,
+    {
+        self.0.send_with(f).await
+    }
+
+    /// Wait for a message, then remove it after `f` reads it in place in the
+    /// channel's buffer, avoiding a move of a large message through the
+    /// stack.
+    ///
+    /// The channel is locked while `f` runs, so it should be quick.
+    #[inline(always)]
+    pub async fn recv_with<R>(
+        &self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, RecvError> {
+        self.0.recv_with(f).await
+    }
+
+    /// Send a message if there is room, without waiting.
+    ///
+    /// ```rust
+    /// use whisk::{Channel, TryRecvError, TrySendError};
+    ///
+    /// let channel = Channel::new();
+    ///
+    /// assert_eq!(channel.try_recv(), Err(TryRecvError::Empty));
+    /// assert_eq!(channel.try_send(1), Ok(()));
+    /// assert_eq!(channel.try_send(2), Err(TrySendError::Full(2)));
+    /// assert_eq!(channel.try_recv(), Ok(1));
+    /// channel.close();
+    /// assert_eq!(channel.try_send(3), Err(TrySendError::Closed(3)));
+    /// assert_eq!(channel.try_recv(), Err(TryRecvError::Closed));
+    /// ```
+    #[inline(always)]
+    pub fn try_send(&self, message: T) -> Result<(), crate::TrySendError<T>> {
+        self.0.try_send(message)
+    }
+
+    /// Send a message if there is room, without waiting, returning the
+    /// message dropped by the channel's
+    /// [`OverflowPolicy`](crate::OverflowPolicy) if any.
+    #[inline(always)]
+    pub fn replace(
+        &self,
+        message: T,
+    ) -> Result<Option<T>, crate::TrySendError<T>> {
+        self.0.replace(message)
+    }
+
+    /// Receive a message if one is available, without waiting.
+    #[inline(always)]
+    pub fn try_recv(&self) -> Result<T, crate::TryRecvError> {
+        self.0.try_recv()
+    }
+
+    /// Send a message, parking the current thread until there is room.
+    ///
+    /// This must not be called from async code, as it blocks the executor.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     let channel = Channel::new();
+    ///     let worker = channel.clone();
+    ///     let worker = std::thread::spawn(move || {
+    ///         pasts::Executor::default().block_on(async move {
+    ///             let request = worker.recv().await.unwrap();
+    ///
+    ///             worker.send(request * 2).await.unwrap();
+    ///         })
+    ///     });
+    ///
+    ///     channel.send_blocking(21).unwrap();
+    ///     worker.join().unwrap();
+    ///     assert_eq!(channel.recv_blocking(), Ok(42));
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn send_blocking(&self, message: T) -> Result<(), SendError<T>> {
+        self.0.send_blocking(message)
+    }
+
+    /// Receive a message, parking the current thread until one is available.
+    ///
+    /// This must not be called from async code, as it blocks the executor.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn recv_blocking(&self) -> Result<T, RecvError> {
+        self.0.recv_blocking()
+    }
+
+    /// Receive a message if one is available, otherwise register to be woken
+    /// when one might be.
+    ///
+    /// Taking the message or registering the waker happens under one lock, so
+    /// there is no window where a wakeup can be lost.  This is the building
+    /// block for custom futures and streams, and is what the [`Future`]
+    /// implementation for `Channel` calls.
+    ///
+    /// ```rust
+    /// use std::{
+    ///     pin::Pin,
+    ///     task::{Context, Poll},
+    /// };
+    ///
+    /// use whisk::Channel;
+    ///
+    /// /// Stream of messages, doubled
+    /// struct Doubled(Channel<u32>);
+    ///
+    /// impl futures::Stream for Doubled {
+    ///     type Item = u32;
+    ///
+    ///     fn poll_next(
+    ///         self: Pin<&mut Self>,
+    ///         cx: &mut Context<'_>,
+    ///     ) -> Poll<Option<u32>> {
+    ///         self.get_mut().0.poll_recv(cx).map(|r| r.ok().map(|n| n * 2))
+    ///     }
+    /// }
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     use futures::StreamExt;
+    ///
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let mut doubled = Doubled(channel.clone());
+    ///         let sending = async {
+    ///             channel.send(1).await.unwrap();
+    ///             channel.send(2).await.unwrap();
+    ///             channel.seal();
+    ///         };
+    ///         let receiving = async {
+    ///             assert_eq!(doubled.next().await, Some(2));
+    ///             assert_eq!(doubled.next().await, Some(4));
+    ///             assert_eq!(doubled.next().await, None);
+    ///         };
+    ///
+    ///         futures::join!(sending, receiving);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, RecvError>> {
+        self.0.poll_recv(cx, &mut self.1)
+    }
+
+    /// Poll to send the message in `message`, registering the waker of `cx`
+    /// if the channel is full.
+    ///
+    /// The message is taken out of `message` once sent, or returned in the
+    /// error if the channel is closed, and is left in place while pending.
+    /// Like [`Channel::poll_recv()`], this is a building block for custom
+    /// futures and sinks.
+    ///
+    /// # Panics
+    ///
+    /// If `message` is `None`.
+    ///
+    /// ```rust
+    /// use std::future;
+    ///
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let mut sender = channel.clone();
+    ///         let mut message = Some(5);
+    ///         let sending =
+    ///             future::poll_fn(|cx| sender.poll_send(cx, &mut message));
+    ///         let (sent, received) =
+    ///             futures::future::join(sending, channel.recv()).await;
+    ///
+    ///         sent.unwrap();
+    ///         assert_eq!(received, Ok(5));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn poll_send(
+        &mut self,
+        cx: &mut Context<'_>,
+        message: &mut Option<T>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        let Self(queue, _, _, wh, parked, ..) = self;
+
+        queue.poll_send(cx, wh, message, parked)
+    }
+
+    /// Close this channel, waking all waiting senders and receivers.
+    ///
+    /// Once closed, sending returns [`SendError`](crate::SendError) and
+    /// receiving returns [`RecvError`].
+    #[inline(always)]
+    pub fn close(&self) {
+        self.0.close()
+    }
+
+    /// Close this channel, and collect the messages still in transit.
+    ///
+    /// Closing and collecting happen under one lock, so every message is
+    /// either received, collected, or returned to its sender in a
+    /// [`SendError`].
+    ///
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use whisk::{Channel, SendError};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     let channel = Channel::new();
+    ///     let sender = std::thread::spawn({
+    ///         let channel = channel.clone();
+    ///
+    ///         move || {
+    ///             block_on(async move {
+    ///                 for i in 0.. {
+    ///                     if let Err(SendError(i)) = channel.send(i).await {
+    ///                         return i;
+    ///                     }
+    ///                 }
+    ///                 unreachable!()
+    ///             })
+    ///         }
+    ///     });
+    ///     let mut seen = block_on(async {
+    ///         let mut received = Vec::new();
+    ///
+    ///         channel.set_capacity(4);
+    ///         for _ in 0..10 {
+    ///             received.push(channel.recv().await.unwrap());
+    ///         }
+    ///
+    ///         received
+    ///     });
+    ///
+    ///     seen.extend(channel.close_and_collect());
+    ///
+    ///     let unsent = sender.join().unwrap();
+    ///
+    ///     // No message is lost or seen twice
+    ///     assert_eq!(seen, (0..unsent).collect::<Vec<_>>());
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn close_and_collect(&self) -> alloc::vec::Vec<T> {
+        self.0.close_and_collect()
+    }
+
+    /// Return true if this channel has been closed.
+    #[inline(always)]
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Seal this channel, so that no more messages can be sent, waking all
+    /// waiting senders and receivers.
+    ///
+    /// Once sealed, sending returns [`SendError`], while receiving drains
+    /// the messages already in transit before returning [`RecvError`].
+    ///
+    /// ```rust
+    /// use whisk::{Channel, RecvError};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///
+    ///         channel.set_capacity(2);
+    ///         channel.send(1).await.unwrap();
+    ///         channel.send(2).await.unwrap();
+    ///         channel.seal();
+    ///         assert!(channel.is_sealed());
+    ///         assert_eq!(channel.send(3).await.unwrap_err().0, 3);
+    ///
+    ///         // Messages sent before sealing are still received
+    ///         assert_eq!(channel.recv().await, Ok(1));
+    ///         assert_eq!(channel.recv().await, Ok(2));
+    ///         assert_eq!(channel.recv().await, Err(RecvError));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn seal(&self) {
+        self.0.seal()
+    }
+
+    /// Return true if this channel has been sealed.
+    #[inline(always)]
+    pub fn is_sealed(&self) -> bool {
+        self.0.is_sealed()
+    }
+
+    /// Return true if a panic while this channel was locked closed it.
+    ///
+    /// This can only happen if a closure passed to the channel, or a waker,
+    /// panics.  Afterwards the channel behaves as if closed, so waiting tasks
+    /// return errors rather than waiting forever.  Operations that don't
+    /// wait, and operations with a timeout, return a `Poisoned` error rather
+    /// than `Closed`, so a crash can be told apart from a clean close.
+    ///
+    /// ```rust
+    /// use std::panic::{self, AssertUnwindSafe};
+    ///
+    /// use whisk::{Channel, RecvError, TryRecvError, TrySendError};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///
+    ///         channel.send(1).await.unwrap();
+    ///
+    ///         let peeking = || channel.peek_with(|_| panic!("oops"));
+    ///
+    ///         assert!(panic::catch_unwind(AssertUnwindSafe(peeking)).is_err());
+    ///         assert!(channel.is_poisoned());
+    ///         assert_eq!(channel.recv().await, Err(RecvError));
+    ///         assert_eq!(channel.try_recv(), Err(TryRecvError::Poisoned));
+    ///         assert_eq!(channel.try_send(2), Err(TrySendError::Poisoned(2)));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn is_poisoned(&self) -> bool {
+        self.0.is_poisoned()
+    }
+
+    /// Get the number of messages in transit.
+    ///
+    /// Other handles may send or receive at any time, so this is only a
+    /// snapshot, useful for metrics and upstream backpressure decisions.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::with_capacity(2);
+    ///
+    ///         assert!(channel.is_empty());
+    ///         channel.send(1).await.unwrap();
+    ///         assert_eq!(channel.len(), 1);
+    ///         assert!(!channel.is_full());
+    ///         channel.send(2).await.unwrap();
+    ///         assert!(channel.is_full());
+    ///         assert_eq!(channel.capacity(), 2);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Return true if there are no messages in transit.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Return true if sending would wait for room.
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.0.is_full()
+    }
+
+    /// Get the maximum number of messages that can be in transit at once.
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Change the maximum number of messages that can be in transit at once.
+    ///
+    /// When growing, waiting senders are woken.  When shrinking below the
+    /// number of messages currently in transit, no messages are discarded, but
+    /// sending waits until the channel has drained below the new capacity.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    ///
+    /// ```rust
+    /// use futures::poll;
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///
+    ///         // Grow, allowing senders to get ahead
+    ///         channel.set_capacity(3);
+    ///         for i in 0..3 {
+    ///             channel.send(i).await.unwrap();
+    ///         }
+    ///
+    ///         let mut sending = channel.send(3);
+    ///
+    ///         assert!(poll!(&mut sending).is_pending());
+    ///
+    ///         // Shrink back to a rendezvous, draining existing messages first
+    ///         channel.set_capacity(1);
+    ///         for i in 0..3 {
+    ///             assert_eq!(channel.recv().await, Ok(i));
+    ///         }
+    ///         assert!(poll!(&mut sending).is_ready());
+    ///
+    ///         // Grow with a waiting sender
+    ///         let mut sending = channel.send(4);
+    ///
+    ///         assert!(poll!(&mut sending).is_pending());
+    ///         channel.set_capacity(2);
+    ///         assert!(poll!(&mut sending).is_ready());
+    ///         assert_eq!(channel.recv().await, Ok(3));
+    ///         assert_eq!(channel.recv().await, Ok(4));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn set_capacity(&self, capacity: usize) {
+        self.0.set_capacity(capacity)
+    }
+
+    /// Wait until `n` slots are free, then reserve them for sending a batch of
+    /// messages that is received contiguously.
+    ///
+    /// Returns [`SendError`] if the channel has been closed or sealed.  Waits
+    /// until the capacity allows for `n` messages, so `n` should not exceed it.
+    ///
+    /// ```rust
+    /// use futures::poll;
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///
+    ///         channel.set_capacity(4);
+    ///
+    ///         let mut reservation = channel.reserve(3).await.unwrap();
+    ///         let mut sending = channel.send(0);
+    ///
+    ///         // Other senders wait for the reservation
+    ///         assert!(poll!(&mut sending).is_pending());
+    ///         for part in 1..=3 {
+    ///             reservation.send(part).unwrap();
+    ///         }
+    ///         sending.await.unwrap();
+    ///
+    ///         for expected in [1, 2, 3, 0] {
+    ///             assert_eq!(channel.recv().await, Ok(expected));
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn reserve(
+        &self,
+        n: usize,
+    ) -> Result<Reservation<'_, T, U>, SendError<()>> {
+        self.0.reserve(n).await
+    }
+
+    /// Wait until a slot is free, then reserve it for sending one message.
+    ///
+    /// Returns [`SendError`] if the channel has been closed or sealed.  Unlike
+    /// a batch reservation, other senders can still use the remaining free
+    /// slots, and any number of permits can be held at once.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let permit = channel.reserve_one().await.unwrap();
+    ///
+    ///         // Only build the message once there's room for it
+    ///         permit.send(vec![0u8; 1024]);
+    ///         assert_eq!(channel.recv().await.unwrap().len(), 1024);
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// Each permit holds one slot, so several can be held at once, and other
+    /// senders use the slots left over.
+    ///
+    /// ```rust
+    /// use futures::poll;
+    /// use whisk::{Channel, TrySendError};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::with_capacity(3);
+    ///         let first = channel.reserve_one().await.unwrap();
+    ///         let second = channel.reserve_one().await.unwrap();
+    ///         let mut third = Box::pin(channel.reserve_one());
+    ///
+    ///         channel.send(1).await.unwrap();
+    ///         assert_eq!(channel.try_send(4), Err(TrySendError::Full(4)));
+    ///         assert!(poll!(&mut third).is_pending());
+    ///         second.send(2);
+    ///         first.send(3);
+    ///
+    ///         for expected in [1, 2, 3] {
+    ///             assert_eq!(channel.recv().await, Ok(expected));
+    ///         }
+    ///
+    ///         third.await.unwrap().send(4);
+    ///         assert_eq!(channel.recv().await, Ok(4));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn reserve_one(&self) -> Result<Permit<'_, T, U>, SendError<()>> {
+        self.0.reserve_one().await
+    }
+
+    /// Wait until a slot is free, then reserve it for sending one message,
+    /// with a permit that can be moved into a spawned task.
+    ///
+    /// Returns [`SendError`] if the channel has been closed or sealed.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let channel = Channel::new();
+    ///     let permit = channel.reserve_owned().await.unwrap();
+    ///
+    ///     tokio::spawn(async move { permit.send("done") });
+    ///     assert_eq!(channel.recv().await, Ok("done"));
+    /// }
+    /// ```
+    pub async fn reserve_owned(
+        &self,
+    ) -> Result<OwnedPermit<T, U>, SendError<()>> {
+        OwnedPermit::new(Arc::clone(&self.0)).await
+    }
+
+    /// Install a callback invoked whenever a sender parks waiting for a free
+    /// slot, and when it stops waiting.
+    ///
+    /// Replaces any previously installed callback.  The callback is invoked
+    /// outside of the channel's lock.
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use futures::poll;
+    /// use whisk::{BackpressureEvent, Channel};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let events = Arc::new(Mutex::new(Vec::new()));
+    ///
+    ///         channel.on_backpressure({
+    ///             let events = events.clone();
+    ///
+    ///             move |event| events.lock().unwrap().push(event)
+    ///         });
+    ///         channel.send(1).await.unwrap();
+    ///
+    ///         let mut sending = channel.send(2);
+    ///
+    ///         assert!(poll!(&mut sending).is_pending());
+    ///         assert_eq!(channel.recv().await, Ok(1));
+    ///         sending.await.unwrap();
+    ///         assert_eq!(
+    ///             *events.lock().unwrap(),
+    ///             [
+    ///                 BackpressureEvent::Parked { occupancy: 1 },
+    ///                 BackpressureEvent::Unparked { occupancy: 1 },
+    ///             ],
+    ///         );
+    ///     });
+    /// }
+    /// ```
+    ///
+    /// A handle polling with [`Channel::poll_send()`] parks once, no matter
+    /// how many times it's polled while full.
+    ///
+    /// ```rust
+    /// use std::{
+    ///     future,
+    ///     sync::{Arc, Mutex},
+    ///     task::Poll,
+    /// };
+    ///
+    /// use whisk::{BackpressureEvent, Channel};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let mut sender = channel.clone();
+    ///         let events = Arc::new(Mutex::new(Vec::new()));
+    ///
+    ///         channel.on_backpressure({
+    ///             let events = events.clone();
+    ///
+    ///             move |event| events.lock().unwrap().push(event)
+    ///         });
+    ///         channel.send(1).await.unwrap();
+    ///
+    ///         let mut message = Some(2);
+    ///
+    ///         for _ in 0..2 {
+    ///             future::poll_fn(|cx| {
+    ///                 let poll = sender.poll_send(cx, &mut message);
+    ///
+    ///                 assert!(poll.is_pending());
+    ///                 Poll::Ready(())
+    ///             })
+    ///             .await;
+    ///         }
+    ///
+    ///         assert_eq!(channel.recv().await, Ok(1));
+    ///         future::poll_fn(|cx| sender.poll_send(cx, &mut message))
+    ///             .await
+    ///             .unwrap();
+    ///         assert_eq!(
+    ///             *events.lock().unwrap(),
+    ///             [
+    ///                 BackpressureEvent::Parked { occupancy: 1 },
+    ///                 BackpressureEvent::Unparked { occupancy: 1 },
+    ///             ],
+    ///         );
+    ///     });
+    /// }
+    /// ```
+    #[cfg(feature = "backpressure")]
+    #[inline(always)]
+    pub fn on_backpressure(
+        &self,
+        f: impl Fn(crate::BackpressureEvent) + core::marker::Send + Sync + 'static,
+    ) {
+        self.0.on_backpressure(f)
+    }
+
+    /// Remove the callback installed with [`Channel::on_backpressure()`].
+    #[cfg(feature = "backpressure")]
+    #[inline(always)]
+    pub fn clear_backpressure(&self) {
+        self.0.clear_backpressure()
+    }
+
+    /// Receive a message from this channel, or time out once `deadline` is
+    /// reaped by `reaper`.
+    ///
+    /// See [`Reaper`](crate::Reaper) for an example.
+    #[inline(always)]
+    pub async fn recv_by<I: Ord + Clone>(
+        &self,
+        reaper: &crate::Reaper<I>,
+        deadline: I,
+    ) -> Result<T, crate::RecvTimeoutError> {
+        self.0.recv_by(reaper, deadline).await
+    }
+
+    /// Receive a message from this channel, or time out once a timer from
+    /// `sleep` for `timeout` completes.
+    ///
+    /// Any function returning a timer future, such as an async runtime's
+    /// `sleep()`, can be used.
+    ///
+    /// ```rust
+    /// use core::{future, time::Duration};
+    ///
+    /// use whisk::{Channel, RecvTimeoutError, SendTimeoutError};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let expired = |_| future::ready(());
+    ///         let timeout = Duration::from_millis(10);
+    ///
+    ///         channel.send_timeout(1, &expired, timeout).await.unwrap();
+    ///         assert_eq!(
+    ///             channel.send_timeout(2, &expired, timeout).await,
+    ///             Err(SendTimeoutError::Timeout(2)),
+    ///         );
+    ///         assert_eq!(channel.recv_timeout(&expired, timeout).await, Ok(1));
+    ///         assert_eq!(
+    ///             channel.recv_timeout(&expired, timeout).await,
+    ///             Err(RecvTimeoutError::Timeout),
+    ///         );
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn recv_timeout(
+        &self,
+        sleep: &impl crate::Sleep,
+        timeout: core::time::Duration,
+    ) -> Result<T, crate::RecvTimeoutError> {
+        self.0.recv_timeout(sleep, timeout).await
+    }
+
+    /// Receive a message from this channel, or time out once `deadline`
+    /// completes.
+    ///
+    /// Whichever finishes first, the receive's waker registration is removed
+    /// before returning, so repeated timeouts don't accumulate wakers.
+    ///
+    /// ```rust
+    /// use whisk::{Channel, RecvTimeoutError};
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let samples = Channel::new();
+    ///         let period = Channel::new();
+    ///         let deadline = async { period.recv().await };
+    ///         let ticking = async { period.send(()).await.unwrap() };
+    ///         let (received, ()) = futures::join!(
+    ///             samples.recv_deadline(deadline),
+    ///             ticking,
+    ///         );
+    ///
+    ///         assert_eq!(received, Err(RecvTimeoutError::Timeout));
+    ///
+    ///         samples.send(0.5).await.unwrap();
+    ///         assert_eq!(
+    ///             samples.recv_deadline(period.recv()).await,
+    ///             Ok(0.5),
+    ///         );
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn recv_deadline(
+        &self,
+        deadline: impl Future,
+    ) -> Result<T, crate::RecvTimeoutError> {
+        self.0.recv_deadline(deadline).await
+    }
+
+    /// Receive a message from this channel, or `None` once `token` is
+    /// cancelled.
+    ///
+    /// A message that is already available is received even if `token` has
+    /// been cancelled.
+    #[inline(always)]
+    pub async fn recv_until_cancelled(
+        &self,
+        token: &CancelToken,
+    ) -> Option<Result<T, RecvError>> {
+        self.0.recv_until_cancelled(token).await
+    }
+
+    /// Send a message on this channel, or time out once a timer from `sleep`
+    /// for `timeout` completes.
+    #[inline(always)]
+    pub async fn send_timeout(
+        &self,
+        message: T,
+        sleep: &impl crate::Sleep,
+        timeout: core::time::Duration,
+    ) -> Result<(), crate::SendTimeoutError<T>> {
+        self.0.send_timeout(message, sleep, timeout).await
+    }
+
+    /// Get the rolling estimate of messages sent per second over the last
+    /// second.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///
+    ///         assert_eq!(channel.throughput(), 0.0);
+    ///
+    ///         for i in 0..4 {
+    ///             channel.send(i).await.unwrap();
+    ///             channel.recv().await.unwrap();
+    ///         }
+    ///
+    ///         assert!(channel.throughput() > 0.0);
+    ///     });
+    /// }
+    /// ```
+    #[cfg(feature = "metrics")]
+    #[inline(always)]
+    pub fn throughput(&self) -> f64 {
+        self.0.throughput()
+    }
+
+    /// Receive messages from this channel, awaiting `handler` on each before
+    /// receiving the next, until closed.
+    ///
+    /// The channel doesn't advance while `handler` is busy, so backpressure
+    /// flows through to senders.
+    ///
+    /// ```rust
+    /// use std::cell::RefCell;
+    ///
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let written = RefCell::new(Vec::new());
+    ///         let sending = async {
+    ///             for i in 0..3 {
+    ///                 channel.send(i).await.unwrap();
+    ///             }
+    ///             channel.seal();
+    ///         };
+    ///         let draining = channel.drain_to(|i| {
+    ///             let written = &written;
+    ///
+    ///             async move { written.borrow_mut().push(i) }
+    ///         });
+    ///
+    ///         futures::join!(sending, draining);
+    ///         assert_eq!(written.into_inner(), [0, 1, 2]);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn drain_to<F: Future<Output = ()>>(
+        &self,
+        handler: impl FnMut(T) -> F,
+    ) {
+        self.0.drain_to(handler).await
+    }
+
+    /// Receive messages from this channel, passing each to `f`, until one
+    /// satisfies `is_last`.
+    ///
+    /// The last message is passed to `f` as well, and no messages are
+    /// received after it.  Returns [`RecvError`] if closed before the last
+    /// message is received.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::<Vec<u8>>::new();
+    ///         let sender = channel.clone();
+    ///         let sending = async move {
+    ///             sender.send(b"Hello".to_vec()).await.unwrap();
+    ///             sender.send(b", world".to_vec()).await.unwrap();
+    ///             sender.send(Vec::new()).await.unwrap();
+    ///             sender.send(b"Next".to_vec()).await.unwrap();
+    ///         };
+    ///         let receiving = async {
+    ///             let mut assembled = Vec::new();
+    ///
+    ///             channel
+    ///                 .recv_until(Vec::is_empty, |chunk| {
+    ///                     assembled.extend(chunk)
+    ///                 })
+    ///                 .await
+    ///                 .unwrap();
+    ///
+    ///             assert_eq!(assembled, b"Hello, world");
+    ///             assert_eq!(channel.recv().await.unwrap(), b"Next");
+    ///         };
+    ///
+    ///         futures::future::join(sending, receiving).await;
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn recv_until(
+        &self,
+        is_last: impl Fn(&T) -> bool,
+        f: impl FnMut(T),
+    ) -> Result<(), RecvError> {
+        self.0.recv_until(is_last, f).await
+    }
+
+    /// Receive the first message that satisfies `f`, leaving the others for
+    /// other receivers.
+    ///
+    /// Waits for a message that satisfies `f` if none are queued.  The
+    /// channel is locked while `f` runs, so it should be quick.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Event {
+    ///     Key(char),
+    ///     Quit,
+    /// }
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let events = Channel::with_capacity(2);
+    ///         let sending = async {
+    ///             events.send(Event::Key('a')).await.unwrap();
+    ///             events.send(Event::Quit).await.unwrap();
+    ///         };
+    ///         let quitting = events.recv_if(|event| *event == Event::Quit);
+    ///         let ((), quit) = futures::join!(sending, quitting);
+    ///
+    ///         assert_eq!(quit, Ok(Event::Quit));
+    ///
+    ///         // Other messages are left for other receivers
+    ///         assert_eq!(events.recv().await, Ok(Event::Key('a')));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn recv_if(
+        &self,
+        f: impl Fn(&T) -> bool,
+    ) -> Result<T, RecvError> {
+        self.0.recv_if(f).await
+    }
+
+    /// Send the message built by `f` from a new reply sender, and wait for
+    /// the reply.
+    ///
+    /// Returns an error if the channel is closed, or if the reply sender was
+    /// dropped without replying.
+    ///
+    /// ```rust
+    /// use whisk::{Channel, OneshotSender};
+    ///
+    /// enum Cmd {
+    ///     /// Tell messenger to add
+    ///     Add(u32, u32, OneshotSender<u32>),
+    /// }
+    ///
+    /// async fn worker_main(commands: Channel<Cmd>) {
+    ///     while let Ok(command) = commands.recv().await {
+    ///         match command {
+    ///             Cmd::Add(a, b, s) => s.send(a + b).unwrap(),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let worker = worker_main(channel.clone());
+    ///         let tasker = async move {
+    ///             let sum = channel.call(|reply| Cmd::Add(43, 400, reply)).await;
+    ///
+    ///             assert_eq!(sum, Ok(443));
+    ///         };
+    ///
+    ///         futures::future::join(worker, tasker).await;
+    ///     });
+    /// }
+    /// ```
+    pub async fn call<R>(
+        &self,
+        f: impl FnOnce(OneshotSender<R>) -> T,
+    ) -> Result<R, RecvError> {
+        let (oneshot, reply) = Oneshot::new();
+
+        self.send(f(reply)).await.map_err(|_| RecvError)?;
+        oneshot.recv().await
+    }
+
+    /// Convert this channel handle into a token that can only send once.
+    #[inline(always)]
+    pub fn into_send_once(self) -> SendOnce<T, U> {
+        self.into()
+    }
+
+    /// Convert this channel handle into a handle that can only receive.
+    #[inline(always)]
+    pub fn into_receiver(self) -> Receiver<T, U> {
+        self.into()
+    }
+
+    /// Convert this channel handle into a handle that can only send.
+    #[inline(always)]
+    pub fn into_sender(self) -> Sender<T, U> {
+        self.into()
+    }
+
+    /// Split this channel handle into a handle that can only send, and a
+    /// handle that can only receive.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let (sender, receiver) = Channel::new().split();
+    ///         let sending = async move {
+    ///             for i in 0..3 {
+    ///                 sender.send(i).await.unwrap();
+    ///             }
+    ///         };
+    ///         let receiving = async {
+    ///             let mut received = Vec::new();
+    ///
+    ///             while let Ok(i) = receiver.recv().await {
+    ///                 received.push(i);
+    ///             }
+    ///
+    ///             received
+    ///         };
+    ///         let ((), received) =
+    ///             futures::future::join(sending, receiving).await;
+    ///
+    ///         assert_eq!(received, [0, 1, 2]);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn split(self) -> (Sender<T, U>, Receiver<T, U>) {
+        (self.clone().into(), self.into())
+    }
+
+    /// Inspect the next message to be received with `f`, without removing
+    /// it.
+    ///
+    /// Returns `None` if there is no message, or if closed.  The channel is
+    /// locked while `f` runs, so it should be quick.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let jobs = Channel::new();
+    ///
+    ///         jobs.send(vec![1, 2, 3]).await.unwrap();
+    ///
+    ///         // Only take jobs small enough for this consumer
+    ///         if jobs.peek_with(|job| job.len() <= 4) == Some(true) {
+    ///             assert_eq!(jobs.recv().await.unwrap(), [1, 2, 3]);
+    ///         }
+    ///
+    ///         assert_eq!(jobs.peek_with(Vec::len), None);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn peek_with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.0.peek_with(f)
+    }
+}
+
+impl<T: Clone, U: ?Sized> Channel<T, U> {
+    /// Clone the next message to be received, without removing it.
+    ///
+    /// Returns `None` if there is no message, or if closed.  The message is
+    /// cloned while the channel is locked, so cloning a large message delays
+    /// other senders and receivers.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///
+    ///         assert_eq!(channel.peek_clone(), None);
+    ///         channel.send("state".to_string()).await.unwrap();
+    ///         assert_eq!(channel.peek_clone().as_deref(), Some("state"));
+    ///         assert_eq!(channel.recv().await.unwrap(), "state");
+    ///         assert_eq!(channel.peek_clone(), None);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn peek_clone(&self) -> Option<T> {
+        self.0.peek_clone()
+    }
+}
+
+impl<T: AsRef<[u8]>, U: ?Sized> Channel<T, U> {
+    /// Receive a message from this channel, and decode a borrow of its bytes
+    /// with `decode`.
+    ///
+    /// The message is dropped after decoding.
+    ///
+    /// ```rust
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::<Vec<u8>>::new();
+    ///         let sending = channel.send(42u32.to_le_bytes().to_vec());
+    ///         let receiving = channel.recv_decode(|bytes| {
+    ///             u32::from_le_bytes(bytes.try_into().unwrap())
+    ///         });
+    ///         let (sent, received) =
+    ///             futures::future::join(sending, receiving).await;
+    ///
+    ///         sent.unwrap();
+    ///         assert_eq!(received, Ok(42));
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub async fn recv_decode<R>(
+        &self,
+        decode: impl FnOnce(&[u8]) -> R,
+    ) -> Result<R, RecvError> {
+        self.0.recv_decode(decode).await
+    }
+}
+
+impl<Q, A, U: ?Sized> Channel<Request<Q, A>, U> {
+    /// Send a query, and wait for the handler to
+    /// [`respond()`](Request::respond) to it.
+    ///
+    /// Returns an error if the channel is closed, or if the handler dropped
+    /// the request without responding.
+    ///
+    /// ```rust
+    /// use whisk::{actor::Request, Channel};
+    ///
+    /// async fn worker_main(requests: Channel<Request<String, usize>>) {
+    ///     while let Ok(request) = requests.recv().await {
+    ///         let answer = request.get().len();
+    ///
+    ///         request.respond(answer).unwrap();
+    ///     }
+    /// }
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let worker = worker_main(channel.clone());
+    ///         let tasker = async move {
+    ///             let answer = channel.request("hello".to_string()).await;
+    ///
+    ///             assert_eq!(answer, Ok(5));
+    ///         };
+    ///
+    ///         futures::future::join(worker, tasker).await;
+    ///     });
+    /// }
+    /// ```
+    pub async fn request(&self, query: Q) -> Result<A, RecvError> {
+        let (request, oneshot) = Request::new(query);
+
+        self.send(request).await.map_err(|_| RecvError)?;
+        oneshot.recv().await
+    }
+}
+
+impl<T, U: ?Sized> Channel<T, U> {
+    /// Convert into a handle that can do what `role` allows
+    pub(crate) fn into_role(mut self, role: Role) -> Self {
+        let old = core::mem::replace(&mut self.5, role);
+        let disconnected = self.0.data.with(|locked| {
+            locked.add_handles(role, 1);
+            locked.remove_handle(old)
+        });
+
+        self.wake_disconnected(disconnected);
+        self
+    }
+
+    /// Wake receivers and senders that are now disconnected
+    fn wake_disconnected(&self, (receivers, senders): (bool, bool)) {
+        if receivers {
+            self.0.data.wake_all_recv();
+        }
+
+        if senders {
+            self.0.data.wake_all_send();
+        }
     }
 }
 
 impl<T, U: ?Sized> Clone for Channel<T, U> {
     fn clone(&self) -> Self {
-        Self(Arc::clone(&self.0), WakeHandle::new())
+        let role = self.5;
+
+        self.0.data.with(|locked| locked.add_handles(role, 1));
+
+        Self(
+            Arc::clone(&self.0),
+            WakeHandle::new(),
+            false,
+            WakeHandle::new(),
+            false,
+            role,
+            false,
+        )
     }
 }
 
@@ -65,7 +1610,7 @@ impl<T, U: ?Sized> core::fmt::Debug for Channel<T, U> {
     }
 }
 
-impl<T, U: ?Sized + Default> Default for Channel<T, U> {
+impl<T, U: Default> Default for Channel<T, U> {
     fn default() -> Self {
         Self::with(U::default())
     }
@@ -80,12 +1625,50 @@ impl<T, U: ?Sized> core::ops::Deref for Channel<T, U> {
 }
 
 impl<T, U: ?Sized> Future for Channel<T, U> {
-    type Output = T;
+    type Output = Result<T, RecvError>;
 
     #[inline(always)]
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
-        let this = self.get_mut();
-        this.0.data.take(cx, &mut this.1)
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+/// Awaiting a shared reference receives a message, with a nameable future
+///
+/// ```rust
+/// use whisk::{Channel, Recv};
+///
+/// /// Manual state machine holding a pending receive
+/// struct Waiting<'a> {
+///     recv: Recv<'a, u32>,
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let waiting = Waiting {
+///             recv: core::future::IntoFuture::into_future(&channel),
+///         };
+///         let sending = channel.send(42);
+///         let (sent, received) =
+///             futures::future::join(sending, waiting.recv).await;
+///
+///         sent.unwrap();
+///         assert_eq!(received, Ok(42));
+///
+///         channel.send(7).await.unwrap();
+///         assert_eq!((&channel).await, Ok(7));
+///     });
+/// }
+/// ```
+impl<'a, T, U: ?Sized> core::future::IntoFuture for &'a Channel<T, U> {
+    type IntoFuture = Recv<'a, T, U>;
+    type Output = Result<T, RecvError>;
+
+    #[inline(always)]
+    fn into_future(self) -> Recv<'a, T, U> {
+        self.recv()
     }
 }
 
@@ -98,9 +1681,7 @@ impl<T, U: ?Sized> event_iterator::EventIterator for Channel<T, U> {
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<T>> {
-        let this = self.get_mut();
-
-        this.0.data.take(cx, &mut this.1).map(Some)
+        self.get_mut().poll_recv(cx).map(Result::ok)
     }
 }
 
@@ -114,18 +1695,214 @@ impl<T, U: ?Sized> futures_core_3::Stream for Channel<Option<T>, U> {
         cx: &mut Context<'_>,
     ) -> Poll<Option<T>> {
         let this = self.get_mut();
-        this.0.data.take(cx, &mut this.1)
+
+        // Stay finished once the stream has ended
+        if this.2 {
+            return Poll::Ready(None);
+        }
+
+        let Poll::Ready(item) = this.poll_recv(cx) else {
+            return Poll::Pending;
+        };
+        let item = item.ok().flatten();
+
+        this.2 = item.is_none();
+        Poll::Ready(item)
+    }
+}
+
+/// ```rust
+/// use futures::{stream::FusedStream, StreamExt};
+/// use whisk::Channel;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let mut stream = channel.clone();
+///
+///         channel.set_capacity(3);
+///         channel.send(Some(1)).await.unwrap();
+///         channel.send(None).await.unwrap();
+///         channel.send(Some(2)).await.unwrap();
+///
+///         assert_eq!(stream.next().await, Some(1));
+///         assert_eq!(stream.next().await, None);
+///         assert!(stream.is_terminated());
+///         assert_eq!(stream.next().await, None);
+///     });
+/// }
+/// ```
+#[cfg(feature = "futures_core_3")]
+impl<T, U: ?Sized> futures_core_3::FusedStream for Channel<Option<T>, U> {
+    fn is_terminated(&self) -> bool {
+        self.2
+    }
+}
+
+/// Closing the sink seals the channel, so receivers drain the messages sent
+/// before it
+///
+/// ```rust
+/// use futures::{stream, StreamExt};
+/// use whisk::{Channel, RecvError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let sink = channel.clone();
+///         let forwarding = stream::iter([1, 2, 3]).map(Ok).forward(sink);
+///         let receiving = async {
+///             let mut received = Vec::new();
+///
+///             while let Ok(message) = channel.recv().await {
+///                 received.push(message);
+///             }
+///
+///             received
+///         };
+///         let (forwarded, received) = futures::join!(forwarding, receiving);
+///
+///         forwarded.unwrap();
+///         assert_eq!(received, [1, 2, 3]);
+///     });
+/// }
+/// ```
+///
+/// Once ready, a sink holds a slot until it sends, so other senders can't
+/// take it.
+///
+/// ```rust
+/// use futures::{future::poll_fn, poll, SinkExt};
+/// use whisk::{Channel, TrySendError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let mut first = Channel::new();
+///         let mut second = first.clone();
+///
+///         poll_fn(|cx| first.poll_ready_unpin(cx)).await.unwrap();
+///
+///         let ready = poll!(poll_fn(|cx| second.poll_ready_unpin(cx)));
+///
+///         assert!(ready.is_pending());
+///         assert_eq!(second.try_send(2), Err(TrySendError::Full(2)));
+///         first.start_send_unpin(1).unwrap();
+///         assert_eq!(first.recv().await, Ok(1));
+///         poll_fn(|cx| second.poll_ready_unpin(cx)).await.unwrap();
+///     });
+/// }
+/// ```
+#[cfg(feature = "futures_sink_3")]
+impl<T, U: ?Sized> futures_sink_3::Sink<T> for Channel<T, U> {
+    type Error = SendError<T>;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        let Self(queue, _, _, wh, _, _, reserved) = self.get_mut();
+
+        if *reserved {
+            return Poll::Ready(Ok(()));
+        }
+
+        // Closed channels are ready, so `start_send()` can return the message
+        queue.data.poll_send(cx, wh, |locked| {
+            if locked.send_closed() {
+                return Poll::Ready(Ok(()));
+            }
+
+            if locked.is_full() {
+                return Poll::Pending;
+            }
+
+            // Reserve a slot for `start_send()`, as a permit does, unless the
+            // overflow policy makes room anyway
+            if locked.policy.waits() {
+                locked.reserved += 1;
+                *reserved = true;
+            }
+
+            Poll::Ready(Ok(()))
+        })
+    }
+
+    fn start_send(
+        self: Pin<&mut Self>,
+        message: T,
+    ) -> Result<(), SendError<T>> {
+        let this = self.get_mut();
+        let reserved = core::mem::take(&mut this.6);
+        // Fill the slot reserved by `poll_ready()`
+        let ret = this.0.data.with(|locked| {
+            locked.reserved -= usize::from(reserved);
+
+            if locked.send_closed() {
+                return Err(SendError(message));
+            }
+
+            Ok(locked.push(message))
+        });
+        // Drop an overwritten message outside of the lock
+        let ret = ret.map(drop);
+
+        if ret.is_ok() {
+            this.0.data.wake_recv();
+
+            #[cfg(feature = "metrics")]
+            this.0.meter.record();
+        }
+
+        ret
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        self.seal();
+        Poll::Ready(Ok(()))
     }
 }
 
 impl<T, U: ?Sized> From<Arc<Queue<T, U>>> for Channel<T, U> {
     fn from(inner: Arc<Queue<T, U>>) -> Self {
-        Self(inner, WakeHandle::new())
+        // Other `Arc`s aren't tracked, so they keep the channel connected
+        let handles = if Arc::strong_count(&inner) > 1 { 2 } else { 1 };
+
+        inner
+            .data
+            .with(|locked| locked.add_handles(Role::Both, handles));
+
+        Self(
+            inner,
+            WakeHandle::new(),
+            false,
+            WakeHandle::new(),
+            false,
+            Role::Both,
+            false,
+        )
     }
 }
 
 impl<T, U: ?Sized> From<Channel<T, U>> for Arc<Queue<T, U>> {
     fn from(channel: Channel<T, U>) -> Self {
+        // The returned `Arc` isn't tracked, so it keeps the channel connected
+        channel
+            .0
+            .data
+            .with(|locked| locked.add_handles(Role::Both, 1));
         channel.0.clone()
     }
 }
@@ -0,0 +1,125 @@
+//! Conflating channels keeping only the latest value for each key
+//!
+//! Sending a value on a [`Channel`] for a key that hasn't been received yet
+//! replaces the stale value, keeping the key's place in line.  Receivers get
+//! each pending key once, with its newest value, in the order the keys were
+//! first sent.
+//!
+//! ```rust
+//! use whisk::conflate::Channel;
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let quotes = Channel::new();
+//!
+//!         quotes.send("ACME", 100);
+//!         quotes.send("INIT", 42);
+//!         assert_eq!(quotes.send("ACME", 101), Some(100));
+//!
+//!         assert_eq!(quotes.recv().await, ("ACME", 101));
+//!         assert_eq!(quotes.recv().await, ("INIT", 42));
+//!     });
+//! }
+//! ```
+
+use alloc::collections::{BTreeMap, VecDeque};
+use core::{future, task::Poll};
+
+use crate::{atomic::Arc, mutex::Mutex, wake_list::WakeHandle};
+
+/// Pending values protected by the mutex
+struct Locked<K, V> {
+    /// Pending keys, in the order they were first sent
+    order: VecDeque<K>,
+    /// Latest value of each pending key
+    values: BTreeMap<K, V>,
+}
+
+/// Unregisters a canceled receive, passing on its wakeup if woken
+struct Waiting<'a, K, V>(&'a Mutex<Locked<K, V>>, WakeHandle);
+
+impl<K, V> Drop for Waiting<'_, K, V> {
+    fn drop(&mut self) {
+        self.0.cancel_recv(&mut self.1);
+    }
+}
+
+/// An MPMC channel that conflates pending values by key
+///
+/// The number of pending values is bounded by the number of keys, so sending
+/// never waits.
+pub struct Channel<K, V>(Arc<Mutex<Locked<K, V>>>);
+
+impl<K: Ord + Clone, V> Channel<K, V> {
+    /// Create a new conflating channel.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Locked {
+            order: VecDeque::new(),
+            values: BTreeMap::new(),
+        })))
+    }
+
+    /// Send the latest value for `key` on this channel.
+    ///
+    /// Returns the stale value it replaced, if the key was still pending.
+    pub fn send(&self, key: K, value: V) -> Option<V> {
+        let stale = self.0.with(|locked| {
+            let stale = locked.values.insert(key.clone(), value);
+
+            if stale.is_none() {
+                locked.order.push_back(key);
+            }
+
+            stale
+        });
+
+        self.0.wake_recv();
+        stale
+    }
+
+    /// Get the number of pending keys.
+    pub fn len(&self) -> usize {
+        self.0.with(|locked| locked.order.len())
+    }
+
+    /// Return true if there are no pending keys.
+    pub fn is_empty(&self) -> bool {
+        self.0.with(|locked| locked.order.is_empty())
+    }
+
+    /// Receive the oldest pending key with its latest value.
+    pub async fn recv(&self) -> (K, V) {
+        let mut waiting = Waiting(&self.0, WakeHandle::new());
+
+        future::poll_fn(|cx| {
+            self.0.poll_recv(cx, &mut waiting.1, |locked| {
+                let Some(key) = locked.order.pop_front() else {
+                    return Poll::Pending;
+                };
+                let value = locked.values.remove(&key).expect("pending key");
+
+                Poll::Ready((key, value))
+            })
+        })
+        .await
+    }
+}
+
+impl<K, V> Clone for Channel<K, V> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<K: Ord + Clone, V> Default for Channel<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> core::fmt::Debug for Channel<K, V> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
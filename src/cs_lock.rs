@@ -0,0 +1,72 @@
+use core::cell::UnsafeCell;
+
+use critical_section::RestoreState;
+
+/// Lock that stays in a critical section while held
+///
+/// On single-core targets, entering a critical section disables interrupts,
+/// so an interrupt handler can never find the lock held by the code it
+/// interrupted, and channels can be used from interrupt handlers.  Since the
+/// critical section spans from locking to unlocking, it's entered with
+/// `critical_section::acquire()` rather than `critical_section::with()`, and
+/// locks must be released in the reverse order they were acquired, which
+/// scoped locking guarantees.
+pub(crate) struct CsLock {
+    /// True while the lock is held, only accessed within the critical section
+    locked: UnsafeCell<bool>,
+    /// State to restore on unlock, only accessed within the critical section
+    restore: UnsafeCell<RestoreState>,
+}
+
+impl CsLock {
+    /// Create a new unlocked lock
+    pub(crate) const fn new() -> Self {
+        Self {
+            locked: UnsafeCell::new(false),
+            restore: UnsafeCell::new(RestoreState::invalid()),
+        }
+    }
+
+    /// Try to acquire the lock, returning true if acquired
+    ///
+    /// Only fails if the lock is already held from within the same critical
+    /// section, which would otherwise be entered again.
+    pub(crate) fn try_lock(&self) -> bool {
+        // Released below if already locked, otherwise on unlock
+        let restore = unsafe { critical_section::acquire() };
+
+        // Safe because only accessed within the critical section
+        unsafe {
+            if *self.locked.get() {
+                critical_section::release(restore);
+                return false;
+            }
+
+            *self.locked.get() = true;
+            *self.restore.get() = restore;
+        }
+
+        true
+    }
+
+    /// Wait until the lock is acquired
+    pub(crate) fn lock(&self) {
+        let mut attempt = 0;
+
+        while !self.try_lock() {
+            crate::backoff::backoff(attempt);
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    /// Release the lock, leaving the critical section
+    pub(crate) fn unlock(&self) {
+        // Safe because still within the critical section entered on lock
+        unsafe {
+            let restore = *self.restore.get();
+
+            *self.locked.get() = false;
+            critical_section::release(restore);
+        }
+    }
+}
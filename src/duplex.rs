@@ -0,0 +1,98 @@
+use crate::{Channel, Recv, Send, TryRecvError, TrySendError};
+
+/// One end of a bidirectional connection, sending `O` and receiving `I`
+///
+/// Created with [`duplex()`].  Once the other end is dropped, receiving
+/// returns [`RecvError`](crate::RecvError) after the remaining messages are received.
+pub struct Duplex<O, I> {
+    outbound: Channel<O>,
+    inbound: Channel<I>,
+}
+
+impl<O, I> Duplex<O, I> {
+    /// Send a message to the other end.
+    #[inline(always)]
+    pub fn send(&self, message: O) -> Send<'_, O> {
+        self.outbound.send(message)
+    }
+
+    /// Send a message to the other end if there is room, without waiting.
+    #[inline(always)]
+    pub fn try_send(&self, message: O) -> Result<(), TrySendError<O>> {
+        self.outbound.try_send(message)
+    }
+
+    /// Receive a message from the other end.
+    #[inline(always)]
+    pub fn recv(&self) -> Recv<'_, I> {
+        self.inbound.recv()
+    }
+
+    /// Receive a message from the other end if one is available, without
+    /// waiting.
+    #[inline(always)]
+    pub fn try_recv(&self) -> Result<I, TryRecvError> {
+        self.inbound.try_recv()
+    }
+
+    /// Close both directions, waking all waiting senders and receivers on
+    /// either end.
+    pub fn close(&self) {
+        self.outbound.close();
+        self.inbound.close();
+    }
+
+    /// Split into the channels for each direction, outbound first.
+    pub fn into_inner(self) -> (Channel<O>, Channel<I>) {
+        (self.outbound, self.inbound)
+    }
+}
+
+impl<O, I> core::fmt::Debug for Duplex<O, I> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Duplex").finish_non_exhaustive()
+    }
+}
+
+/// Create a bidirectional connection, returning an end sending `A` and
+/// receiving `B`, and an end sending `B` and receiving `A`.
+///
+/// ```rust
+/// use whisk::Duplex;
+///
+/// async fn worker_main(duplex: Duplex<usize, &str>) {
+///     while let Ok(word) = duplex.recv().await {
+///         duplex.send(word.len()).await.unwrap();
+///     }
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let (tasker, worker) = whisk::duplex();
+///         let worker = worker_main(worker);
+///         let tasker = async move {
+///             tasker.send("hello").await.unwrap();
+///             assert_eq!(tasker.recv().await, Ok(5));
+///             tasker.send("hi").await.unwrap();
+///             assert_eq!(tasker.recv().await, Ok(2));
+///         };
+///
+///         futures::future::join(worker, tasker).await;
+///     });
+/// }
+/// ```
+pub fn duplex<A, B>() -> (Duplex<A, B>, Duplex<B, A>) {
+    let (a, b) = (Channel::new(), Channel::new());
+
+    (
+        Duplex {
+            outbound: a.clone(),
+            inbound: b.clone(),
+        },
+        Duplex {
+            outbound: b,
+            inbound: a,
+        },
+    )
+}
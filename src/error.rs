@@ -0,0 +1,225 @@
+use core::fmt;
+
+/// Error returned when sending on a closed or sealed channel, or once every
+/// [`Receiver`](crate::Receiver) has been dropped
+///
+/// Contains the message that couldn't be sent.  A channel closed by a panic
+/// returns this too; check
+/// [`Channel::is_poisoned()`](crate::Channel::is_poisoned) to tell them apart.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a closed channel")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SendError<T> {}
+
+/// Error returned when receiving on a closed channel, or a sealed and drained
+/// channel
+///
+/// A channel closed by a panic returns this too; check
+/// [`Channel::is_poisoned()`](crate::Channel::is_poisoned) to tell them apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("receiving on a closed channel")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecvError {}
+
+/// Error returned when receiving with a deadline
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvTimeoutError {
+    /// The deadline passed before a message was received
+    Timeout,
+    /// The channel was closed, or sealed and drained
+    Closed,
+    /// A panic while the channel was locked closed it
+    Poisoned,
+}
+
+impl From<RecvError> for RecvTimeoutError {
+    fn from(_: RecvError) -> Self {
+        Self::Closed
+    }
+}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => f.write_str("timed out receiving on a channel"),
+            Self::Closed => RecvError.fmt(f),
+            Self::Poisoned => f.write_str("receiving on a poisoned channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecvTimeoutError {}
+
+/// Error returned when sending with a timeout
+///
+/// Contains the message that couldn't be sent.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SendTimeoutError<T> {
+    /// The timeout elapsed before there was room for the message
+    Timeout(T),
+    /// The channel was closed or sealed
+    Closed(T),
+    /// A panic while the channel was locked closed it
+    Poisoned(T),
+}
+
+impl<T> SendTimeoutError<T> {
+    /// Get the message that couldn't be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Timeout(message)
+            | Self::Closed(message)
+            | Self::Poisoned(message) => message,
+        }
+    }
+}
+
+impl<T> From<SendError<T>> for SendTimeoutError<T> {
+    fn from(SendError(message): SendError<T>) -> Self {
+        Self::Closed(message)
+    }
+}
+
+impl<T> fmt::Debug for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout(_) => f.write_str("Timeout(..)"),
+            Self::Closed(_) => f.write_str("Closed(..)"),
+            Self::Poisoned(_) => f.write_str("Poisoned(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for SendTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout(_) => f.write_str("timed out sending on a channel"),
+            Self::Closed(_) => f.write_str("sending on a closed channel"),
+            Self::Poisoned(_) => f.write_str("sending on a poisoned channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SendTimeoutError<T> {}
+
+/// Error returned when trying to send without waiting
+///
+/// Contains the message that couldn't be sent.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// There is no room for the message
+    Full(T),
+    /// The channel was closed or sealed, or every receiver was dropped
+    Closed(T),
+    /// A panic while the channel was locked closed it
+    Poisoned(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Get the message that couldn't be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(message)
+            | Self::Closed(message)
+            | Self::Poisoned(message) => message,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Full(..)"),
+            Self::Closed(_) => f.write_str("Closed(..)"),
+            Self::Poisoned(_) => f.write_str("Poisoned(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("sending on a full channel"),
+            Self::Closed(_) => f.write_str("sending on a closed channel"),
+            Self::Poisoned(_) => f.write_str("sending on a poisoned channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for TrySendError<T> {}
+
+/// Error returned when trying to receive without waiting
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// There is no message to receive
+    Empty,
+    /// The channel was closed, or sealed and drained
+    Closed,
+    /// A panic while the channel was locked closed it
+    Poisoned,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("receiving on an empty channel"),
+            Self::Closed => RecvError.fmt(f),
+            Self::Poisoned => f.write_str("receiving on a poisoned channel"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TryRecvError {}
+
+/// Error returned when receiving from a broadcast
+/// [`Subscriber`](crate::broadcast::Subscriber)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BroadcastRecvError {
+    /// The subscriber fell behind a channel with a capacity, and this many
+    /// messages were dropped before it received them
+    Lagged(u64),
+    /// Every sender was dropped, and all messages have been received
+    Closed,
+}
+
+impl From<RecvError> for BroadcastRecvError {
+    fn from(_: RecvError) -> Self {
+        Self::Closed
+    }
+}
+
+impl fmt::Display for BroadcastRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lagged(n) => write!(f, "receiver lagged by {n} messages"),
+            Self::Closed => RecvError.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BroadcastRecvError {}
@@ -0,0 +1,121 @@
+use core::{future, task::Poll};
+
+use crate::{mutex::Mutex, wake_list::WakeHandle};
+
+/// Event state protected by the mutex
+struct State {
+    /// True if notified with no task waiting
+    permit: bool,
+    /// Incremented each time every waiting task is notified
+    generation: usize,
+}
+
+/// Unregisters a canceled wait, passing on its wakeup if woken
+struct Waiting<'a>(&'a Event, WakeHandle);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.state.cancel_recv(&mut self.1);
+    }
+}
+
+/// A notification without a message, for waking one or all waiting tasks
+///
+/// Unlike a `Channel<()>`, there is no slot for messages, and
+/// [`notify_waiters()`](Event::notify_waiters) wakes every task that is
+/// waiting without leaving a notification for later tasks.
+///
+/// ```rust
+/// use whisk::Event;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let event = Event::new();
+///
+///         // Stored until a task waits
+///         event.notify_one();
+///         event.notified().await;
+///
+///         // Wakes both waiting tasks
+///         let notifying = async { event.notify_waiters() };
+///
+///         futures::join!(event.notified(), event.notified(), notifying);
+///     });
+/// }
+/// ```
+pub struct Event {
+    state: Mutex<State>,
+}
+
+impl Event {
+    const_fn! {
+        /// Create a new event that hasn't been notified.
+        pub const fn new() -> Self {
+            Self {
+                state: Mutex::new(State {
+                    permit: false,
+                    generation: 0,
+                }),
+            }
+        }
+    }
+
+    /// Notify one waiting task, or the next task to wait if none are.
+    ///
+    /// Notifications don't accumulate, so notifying more than once before a
+    /// task waits only notifies one task.
+    pub fn notify_one(&self) {
+        self.state.with(|state| state.permit = true);
+        self.state.wake_recv();
+    }
+
+    /// Notify every task that is currently waiting.
+    ///
+    /// Tasks that start waiting afterwards aren't notified.
+    pub fn notify_waiters(&self) {
+        self.state.with(|state| {
+            state.generation = state.generation.wrapping_add(1);
+        });
+        self.state.wake_all_recv();
+    }
+
+    /// Wait to be notified.
+    pub async fn notified(&self) {
+        let mut waiting = Waiting(self, WakeHandle::new());
+        let mut generation = None;
+
+        future::poll_fn(|cx| {
+            self.state.poll_recv(cx, &mut waiting.1, |state| {
+                if state.permit {
+                    state.permit = false;
+                    return Poll::Ready(());
+                }
+
+                match generation {
+                    Some(generation) if generation != state.generation => {
+                        Poll::Ready(())
+                    }
+                    Some(_) => Poll::Pending,
+                    None => {
+                        generation = Some(state.generation);
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await
+    }
+}
+
+impl Default for Event {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Event {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Event").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{Channel, Queue};
+
+impl<T, U: ?Sized> Queue<T, U> {
+    /// Move a message into `out` if one is available, without waiting.
+    ///
+    /// Returns true if a message was written to `out`, and false if the queue
+    /// is empty or closed.
+    ///
+    /// # Safety
+    ///
+    /// `out` must be non-null, properly aligned, and valid for writes of `T`.
+    /// Any value previously at `out` is overwritten without being dropped, so
+    /// it should be uninitialized.
+    pub unsafe fn recv_into_ptr(&self, out: *mut T) -> bool {
+        let Ok(message) = self.try_recv() else {
+            return false;
+        };
+
+        out.write(message);
+        true
+    }
+}
+
+impl<T, U: ?Sized> Channel<T, U> {
+    /// Move a message into `out` if one is available, without waiting.
+    ///
+    /// Returns true if a message was written to `out`, and false if the
+    /// channel is empty or closed.
+    ///
+    /// # Safety
+    ///
+    /// `out` must be non-null, properly aligned, and valid for writes of `T`.
+    /// Any value previously at `out` is overwritten without being dropped, so
+    /// it should be uninitialized.
+    ///
+    /// ```rust
+    /// use std::mem::MaybeUninit;
+    ///
+    /// use whisk::Channel;
+    ///
+    /// # #[ntest::timeout(1000)]
+    /// fn main() {
+    ///     pasts::Executor::default().block_on(async {
+    ///         let channel = Channel::new();
+    ///         let mut out = MaybeUninit::<u32>::uninit();
+    ///
+    ///         assert!(!unsafe { channel.recv_into_ptr(out.as_mut_ptr()) });
+    ///         channel.send(7).await.unwrap();
+    ///         assert!(unsafe { channel.recv_into_ptr(out.as_mut_ptr()) });
+    ///         assert_eq!(unsafe { out.assume_init() }, 7);
+    ///     });
+    /// }
+    /// ```
+    #[inline(always)]
+    pub unsafe fn recv_into_ptr(&self, out: *mut T) -> bool {
+        self.0.recv_into_ptr(out)
+    }
+}
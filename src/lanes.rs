@@ -0,0 +1,116 @@
+//! Multi-lane channels for message prioritization
+//!
+//! A [`Channel`] has a fixed number of FIFO lanes.  Receiving always drains
+//! lane `0` first, then lane `1`, and so on, preserving send order within each
+//! lane.
+//!
+//! ```rust
+//! use whisk::lanes::Channel;
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let channel = Channel::new(3);
+//!
+//!         channel.send_lane(2, "low a");
+//!         channel.send_lane(1, "normal a");
+//!         channel.send_lane(2, "low b");
+//!         channel.send_lane(0, "high a");
+//!         channel.send_lane(1, "normal b");
+//!
+//!         assert_eq!(channel.recv().await, "high a");
+//!         assert_eq!(channel.recv().await, "normal a");
+//!         assert_eq!(channel.recv().await, "normal b");
+//!         assert_eq!(channel.recv().await, "low a");
+//!         assert_eq!(channel.recv().await, "low b");
+//!     });
+//! }
+//! ```
+
+use alloc::{boxed::Box, collections::VecDeque};
+use core::{future, task::Poll};
+
+use crate::{atomic::Arc, mutex::Mutex, wake_list::WakeHandle};
+
+/// Lanes protected by the mutex
+struct Locked<T> {
+    /// FIFO for each lane, in order of priority
+    lanes: Box<[VecDeque<T>]>,
+}
+
+/// Unregisters a receive when dropped, passing on a wakeup it didn't handle
+struct Receiving<'a, T>(&'a Mutex<Locked<T>>, WakeHandle);
+
+impl<T> Drop for Receiving<'_, T> {
+    fn drop(&mut self) {
+        self.0.cancel_recv(&mut self.1);
+    }
+}
+
+/// An MPMC channel with multiple prioritized lanes
+///
+/// Lanes are unbounded, so sending never waits.
+pub struct Channel<T>(Arc<Mutex<Locked<T>>>);
+
+impl<T> Channel<T> {
+    /// Create a new channel with the specified number of lanes.
+    ///
+    /// # Panics
+    ///
+    /// If `lanes` is zero.
+    pub fn new(lanes: usize) -> Self {
+        assert_ne!(lanes, 0, "Channel must have at least one lane");
+
+        let lanes = (0..lanes).map(|_| VecDeque::new()).collect();
+
+        Self(Arc::new(Mutex::new(Locked { lanes })))
+    }
+
+    /// Get the number of lanes in this channel.
+    pub fn lanes(&self) -> usize {
+        self.0.with(|locked| locked.lanes.len())
+    }
+
+    /// Send a message on the specified lane of this channel.
+    ///
+    /// Lane `0` has the highest priority.
+    ///
+    /// # Panics
+    ///
+    /// If `lane` is out of range.
+    pub fn send_lane(&self, lane: usize, message: T) {
+        self.0.with(|locked| locked.lanes[lane].push_back(message));
+        self.0.wake_recv();
+    }
+
+    /// Receive the highest priority message from this channel.
+    ///
+    /// If canceled after being woken for a message, another waiting receiver
+    /// is woken instead.
+    pub async fn recv(&self) -> T {
+        let mut receiving = Receiving(&self.0, WakeHandle::new());
+
+        future::poll_fn(|cx| {
+            self.0.poll_recv(cx, &mut receiving.1, |locked| {
+                locked
+                    .lanes
+                    .iter_mut()
+                    .find_map(VecDeque::pop_front)
+                    .map_or(Poll::Pending, Poll::Ready)
+            })
+        })
+        .await
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> core::fmt::Debug for Channel<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
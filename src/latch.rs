@@ -0,0 +1,109 @@
+use core::{future, task::Poll};
+
+use crate::{
+    atomic::{Arc, AtomicUsize, Ordering::SeqCst},
+    wake_list::{WakeHandle, WakeList},
+};
+
+struct Shared {
+    /// Number of counts remaining until released
+    count: AtomicUsize,
+    /// List of waiting tasks
+    waiters: WakeList,
+}
+
+/// A countdown latch, releasing every waiting task once counted down to zero
+///
+/// Counting down doesn't wait, so it can be done from `Drop`.  Clones share
+/// the same count.
+///
+/// ```rust
+/// use whisk::Latch;
+///
+/// async fn worker(ready: Latch) {
+///     // Initialize, then signal readiness
+///     ready.count_down();
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let ready = Latch::new(2);
+///         let waiting = async {
+///             ready.wait().await;
+///             assert_eq!(ready.count(), 0);
+///         };
+///
+///         futures::join!(
+///             waiting,
+///             worker(ready.clone()),
+///             worker(ready.clone()),
+///         );
+///     });
+/// }
+/// ```
+pub struct Latch(Arc<Shared>);
+
+impl Latch {
+    /// Create a new latch that is released after `count` counts.
+    pub fn new(count: usize) -> Self {
+        Self(Arc::new(Shared {
+            count: AtomicUsize::new(count),
+            waiters: WakeList::new(),
+        }))
+    }
+
+    /// Count down by one, releasing waiting tasks if the count reaches zero.
+    ///
+    /// Does nothing if already released.
+    pub fn count_down(&self) {
+        let counted = self
+            .0
+            .count
+            .fetch_update(SeqCst, SeqCst, |count| count.checked_sub(1));
+
+        if counted == Ok(1) {
+            self.0.waiters.wake_all();
+        }
+    }
+
+    /// Get the number of counts remaining until released.
+    pub fn count(&self) -> usize {
+        self.0.count.load(SeqCst)
+    }
+
+    /// Wait until the count reaches zero.
+    pub async fn wait(&self) {
+        let mut wh = WakeHandle::new();
+
+        future::poll_fn(|cx| {
+            if self.count() == 0 {
+                return Poll::Ready(());
+            }
+
+            wh.register(&self.0.waiters, cx.waker());
+
+            // Check again in case of release before registering
+            if self.count() == 0 {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl Clone for Latch {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl core::fmt::Debug for Latch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Latch")
+            .field("count", &self.count())
+            .finish_non_exhaustive()
+    }
+}
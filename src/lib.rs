@@ -3,13 +3,54 @@
 //! notifiers, and actors.
 //!
 //! Whisk defines a simple [`Channel`] type rather than splitting into sender /
-//! receiver pairs.  A [`Channel`] can both send and receive.
+//! receiver pairs.  A [`Channel`] can both send and receive.  For the more
+//! familiar pair of a [`Sender`] and a [`Receiver`], use [`channel()`].
 //!
 //! # Optional Features
 //!  - **futures_core_3**: Implement [`Stream`](futures_core_3::Stream) for
-//!    [`Channel`] (generic `T` must be `Option<Item>`)
+//!    [`Channel`] (generic `T` must be `Option<Item>`), [`Receiver`],
+//!    [`Merge`] and [`Demux`], and enable the `stream_ext` module of stream
+//!    combinators
+//!  - **futures_sink_3**: Implement [`Sink`](futures_sink_3::Sink) for
+//!    [`Channel`]
 //!  - **event_iterator**: Implement
 //!    [`EventIterator`](event_iterator::EventIterator) for [`Channel`]
+//!  - **std**: Enable functionality that depends on the standard library,
+//!    such as blocking `send_blocking()` and `recv_blocking()`
+//!  - **metrics**: Enable `throughput()` on [`Channel`] and [`Queue`]
+//!    (requires `std`)
+//!  - **test_util**: Enable the `test_util` module for deterministic testing
+//!    of concurrent interleavings (requires `std`)
+//!  - **backpressure**: Enable `on_backpressure()` on [`Channel`] and
+//!    [`Queue`] for observing senders waiting on a full channel (requires
+//!    `std`)
+//!  - **ffi**: Enable `recv_into_ptr()` on [`Channel`] and [`Queue`] for
+//!    receiving into a caller-provided pointer
+//!  - **fixed_wakers**: Store waiting tasks in a fixed array per direction
+//!    for each channel, instead of allocating wake nodes; beyond that, tasks
+//!    are polled again until a slot frees up.  The array holds 16 tasks, or
+//!    the number set by the `WHISK_FIXED_WAKERS` environment variable at
+//!    build time.  Channels still allocate their shared state and message
+//!    buffers, so `alloc` is still required
+//!  - **pi_lock**: Lock channels with a priority inheritance mutex on Linux,
+//!    instead of spinning, to avoid priority inversion on real-time systems
+//!    (elsewhere, yields to the OS scheduler while waiting; requires `std`)
+//!  - **std_lock**: Lock channels with the standard library's mutex and
+//!    condition variable, instead of spinning, so waiting threads sleep while
+//!    the holder is descheduled (requires `std`)
+//!  - **ticket_lock**: Lock channels with a fair spinlock, serving waiting
+//!    threads in order so none are starved by others repeatedly winning the
+//!    lock
+//!  - **critical_section**: Lock channels by entering a critical section
+//!    with the `critical-section` crate, so channels can be used from
+//!    interrupt handlers on bare-metal targets
+//!  - **tap**: Enable [`Tap`], a [`Channel`] wrapper that logs every message
+//!  - **portable_atomic**: Use the `portable-atomic` crate for atomics and
+//!    reference counting, so whisk compiles on targets without atomic
+//!    compare-and-swap, such as `thumbv6m-none-eabi` (`portable-atomic` must
+//!    be configured for the target, for example with its `critical-section`
+//!    feature; on such targets, `Arc` in public APIs is
+//!    `portable_atomic_util::Arc`)
 //!
 //! # Getting Started
 //!
@@ -21,11 +62,11 @@
 //!     Add(u32, u32, Channel<u32>),
 //! }
 //!
-//! async fn worker_main(commands: Channel<Option<Cmd>>) {
-//!     while let Some(command) = commands.recv().await {
+//! async fn worker_main(commands: Channel<Cmd>) {
+//!     while let Ok(command) = commands.recv().await {
 //!         println!("Worker receiving command");
 //!         match command {
-//!             Cmd::Add(a, b, s) => s.send(a + b).await,
+//!             Cmd::Add(a, b, s) => s.send(a + b).await.unwrap(),
 //!         }
 //!     }
 //!
@@ -43,14 +84,14 @@
 //!     // Do an addition
 //!     println!("Sending command…");
 //!     let oneshot = Channel::new();
-//!     channel.send(Some(Cmd::Add(43, 400, oneshot.clone()))).await;
+//!     channel.send(Cmd::Add(43, 400, oneshot.clone())).await.unwrap();
 //!     println!("Receiving response…");
-//!     let response = oneshot.recv().await;
+//!     let response = oneshot.recv().await.unwrap();
 //!     assert_eq!(response, 443);
 //!
 //!     // Tell worker to stop
 //!     println!("Stopping worker…");
-//!     channel.send(None).await;
+//!     channel.close();
 //!     println!("Waiting for worker to stop…");
 //!
 //!     worker_thread.join().unwrap();
@@ -87,13 +128,150 @@
 )]
 #![deny(unsafe_code)]
 
+#[cfg(not(any(
+    feature = "portable_atomic",
+    all(target_has_atomic = "8", target_has_atomic = "ptr")
+)))]
+compile_error!(
+    "whisk requires atomic compare-and-swap on the target, or the \
+     `portable_atomic` feature"
+);
+
 extern crate alloc;
+#[cfg(any(feature = "std", loom))]
+extern crate std;
+
+/// Define a `const fn`, which isn't `const` when built with `--cfg loom`,
+/// since loom's atomics can't be created in constant contexts
+macro_rules! const_fn {
+    ($(#[$attr:meta])* $vis:vis const fn $name:ident $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$attr])* $vis const fn $name $($rest)*
+        #[cfg(loom)]
+        $(#[$attr])* $vis fn $name $($rest)*
+    };
+}
 
+pub mod actor;
+mod adapter;
+mod atomic;
+#[allow(unsafe_code)]
+pub mod backoff;
+#[cfg(feature = "backpressure")]
+mod backpressure;
+#[cfg(feature = "std")]
+mod blocking;
+pub mod broadcast;
+pub mod bus;
+mod cancel;
 mod channel;
+pub mod conflate;
+#[cfg(all(
+    feature = "critical_section",
+    not(any(
+        all(feature = "pi_lock", target_os = "linux"),
+        feature = "std_lock",
+        feature = "ticket_lock"
+    ))
+))]
+#[allow(unsafe_code)]
+mod cs_lock;
+mod duplex;
+mod error;
+mod event;
+#[cfg(feature = "ffi")]
+#[allow(unsafe_code)]
+mod ffi;
+pub mod lanes;
+mod latch;
+mod merge;
+#[cfg(feature = "metrics")]
+mod metrics;
 #[allow(unsafe_code)]
 mod mutex;
+mod mux;
+#[allow(unsafe_code)]
+mod once;
+#[allow(unsafe_code)]
+mod oneshot;
+mod overflow;
+#[cfg(all(feature = "pi_lock", target_os = "linux"))]
+#[allow(unsafe_code)]
+mod pi_lock;
+mod pipe;
+pub mod pool;
+pub mod priority;
 mod queue;
+mod reaper;
+mod receiver;
+mod reservation;
+#[allow(unsafe_code)]
+pub mod ring;
+mod scope;
+mod select;
+mod semaphore;
+mod send_once;
+mod sender;
+mod shutdown;
+#[cfg(all(
+    feature = "std_lock",
+    not(all(feature = "pi_lock", target_os = "linux"))
+))]
+mod std_lock;
+#[cfg(feature = "futures_core_3")]
+pub mod stream_ext;
+#[allow(unsafe_code)]
+pub mod sync;
+#[cfg(feature = "tap")]
+mod tap;
+#[cfg(feature = "test_util")]
+pub mod test_util;
+#[cfg(all(
+    feature = "ticket_lock",
+    not(any(
+        all(feature = "pi_lock", target_os = "linux"),
+        feature = "std_lock"
+    ))
+))]
+mod ticket_lock;
+mod traits;
 #[allow(unsafe_code)]
 mod wake_list;
+pub mod watch;
+pub mod wfq;
+mod work_queue;
 
-pub use self::{channel::Channel, queue::Queue};
+#[cfg(feature = "backpressure")]
+pub use self::backpressure::BackpressureEvent;
+#[cfg(feature = "tap")]
+pub use self::tap::Tap;
+pub use self::{
+    adapter::{Filtered, Mapped},
+    cancel::CancelToken,
+    channel::{channel, Channel},
+    duplex::{duplex, Duplex},
+    error::{
+        BroadcastRecvError, RecvError, RecvTimeoutError, SendError,
+        SendTimeoutError, TryRecvError, TrySendError,
+    },
+    event::Event,
+    latch::Latch,
+    merge::{merge, Merge},
+    mux::{Demux, Mux},
+    once::Once,
+    oneshot::{Oneshot, OneshotSender},
+    overflow::OverflowPolicy,
+    pipe::{forward, tee},
+    queue::{Queue, Recv, Send},
+    reaper::Reaper,
+    receiver::Receiver,
+    reservation::{OwnedPermit, Permit, Reservation},
+    scope::channel_scope,
+    select::Select,
+    semaphore::{Semaphore, SemaphorePermit},
+    send_once::SendOnce,
+    sender::Sender,
+    shutdown::Shutdown,
+    traits::{AsyncReceiver, AsyncSender, Sleep},
+    work_queue::{Work, WorkQueue},
+};
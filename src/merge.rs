@@ -0,0 +1,162 @@
+use alloc::vec::Vec;
+use core::{
+    future,
+    task::{Context, Poll},
+};
+
+use crate::Channel;
+
+/// Stream of messages received from any of a set of channels
+///
+/// Created with [`merge()`].  Each channel registers the waiting task with
+/// its own handle, so a message on any of them wakes the task.  Channels are
+/// polled in turn, starting after the one last received from, so a busy
+/// channel can't starve the others.  Once a channel is closed or
+/// disconnected, it is dropped from the set.  Enable the
+/// **`futures_core_3`** feature for `Merge` to implement
+/// [`Stream`](futures_core_3::Stream).
+pub struct Merge<T = (), U: ?Sized = ()> {
+    channels: Vec<Channel<T, U>>,
+    /// Index of the channel to poll first
+    next: usize,
+}
+
+impl<T, U: ?Sized> Merge<T, U> {
+    /// Add another channel to receive from.
+    pub fn push(&mut self, channel: Channel<T, U>) {
+        self.channels.push(channel);
+    }
+
+    /// Return the number of channels still open.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Return true if every channel has been closed or disconnected.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Receive a message from any channel.
+    ///
+    /// Returns `None` once every channel is closed or disconnected.
+    pub async fn recv(&mut self) -> Option<T> {
+        future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Poll for a message from any channel, registering the waker of `cx`
+    /// with each channel if none is available.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        poll_each(&mut self.channels, &mut self.next, cx, |channel| channel)
+            .map(|received| received.map(|(_, message)| message))
+    }
+}
+
+/// Poll the channel of each entry in turn, starting at `next`, returning the
+/// index of the entry received from with the message
+///
+/// Entries with closed or disconnected channels are removed, and `None` is
+/// returned once there are no entries left.
+pub(crate) fn poll_each<E, T, U: ?Sized>(
+    entries: &mut Vec<E>,
+    next: &mut usize,
+    cx: &mut Context<'_>,
+    channel: impl Fn(&mut E) -> &mut Channel<T, U>,
+) -> Poll<Option<(usize, T)>> {
+    let mut remaining = entries.len();
+
+    while remaining > 0 {
+        remaining -= 1;
+
+        if *next >= entries.len() {
+            *next = 0;
+        }
+
+        match channel(&mut entries[*next]).poll_recv(cx) {
+            Poll::Ready(Ok(message)) => {
+                let index = *next;
+
+                *next += 1;
+                return Poll::Ready(Some((index, message)));
+            }
+            Poll::Ready(Err(_)) => {
+                entries.remove(*next);
+            }
+            Poll::Pending => *next += 1,
+        }
+    }
+
+    if entries.is_empty() {
+        return Poll::Ready(None);
+    }
+
+    Poll::Pending
+}
+
+impl<T, U: ?Sized> core::fmt::Debug for Merge<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Merge")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "futures_core_3")]
+impl<T, U: ?Sized> futures_core_3::Stream for Merge<T, U> {
+    type Item = T;
+
+    fn poll_next(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+#[cfg(feature = "futures_core_3")]
+impl<T, U: ?Sized> futures_core_3::FusedStream for Merge<T, U> {
+    fn is_terminated(&self) -> bool {
+        self.is_empty()
+    }
+}
+
+/// Receive from any of a set of channels with the same message type.
+///
+/// ```rust
+/// use whisk::Channel;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let a = Channel::new();
+///         let b = Channel::new();
+///         let mut merged = whisk::merge([a.clone(), b.clone()]);
+///         let sending = async {
+///             a.send(1).await.unwrap();
+///             b.send(2).await.unwrap();
+///             a.seal();
+///             b.seal();
+///         };
+///         let receiving = async {
+///             let mut total = 0;
+///
+///             while let Some(message) = merged.recv().await {
+///                 total += message;
+///             }
+///
+///             total
+///         };
+///         let ((), total) = futures::join!(sending, receiving);
+///
+///         assert_eq!(total, 3);
+///     });
+/// }
+/// ```
+pub fn merge<T, U: ?Sized>(
+    channels: impl IntoIterator<Item = Channel<T, U>>,
+) -> Merge<T, U> {
+    Merge {
+        channels: channels.into_iter().collect(),
+        next: 0,
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Duration of each bucket of sends
+const BUCKET: Duration = Duration::from_millis(125);
+/// Number of buckets in the rolling window
+const BUCKETS: usize = 8;
+
+/// Ring of `(bucket start, send count)` samples
+struct Ring {
+    samples: [Option<(Instant, u64)>; BUCKETS],
+    head: usize,
+}
+
+/// Rolling throughput meter
+pub(crate) struct Meter(Mutex<Ring>);
+
+impl Meter {
+    const_fn! {
+        /// Create a new meter
+        pub(crate) const fn new() -> Self {
+            Self(Mutex::new(Ring {
+                samples: [None; BUCKETS],
+                head: 0,
+            }))
+        }
+    }
+
+    /// Record a sent message
+    pub(crate) fn record(&self) {
+        let now = Instant::now();
+        let mut ring = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let head = ring.head;
+
+        match &mut ring.samples[head] {
+            Some((start, count)) if now.duration_since(*start) < BUCKET => {
+                *count += 1;
+            }
+            sample => {
+                if sample.is_some() {
+                    ring.head = (head + 1) % BUCKETS;
+                }
+
+                let head = ring.head;
+
+                ring.samples[head] = Some((now, 1));
+            }
+        }
+    }
+
+    /// Get messages per second over the window
+    pub(crate) fn throughput(&self) -> f64 {
+        let now = Instant::now();
+        let ring = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let window = BUCKET * BUCKETS as u32;
+        let mut total = 0;
+        let mut oldest = now;
+
+        for (start, count) in ring.samples.iter().flatten() {
+            if now.duration_since(*start) < window {
+                total += count;
+                oldest = oldest.min(*start);
+            }
+        }
+
+        let elapsed = now.duration_since(oldest).max(BUCKET);
+
+        total as f64 / elapsed.as_secs_f64()
+    }
+}
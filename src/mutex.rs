@@ -1,20 +1,160 @@
 use core::{
-    cell::{Cell, UnsafeCell},
-    sync::atomic::{
-        AtomicBool,
-        Ordering::{Acquire, Release},
-    },
+    cell::UnsafeCell,
+    mem,
     task::{Context, Poll},
 };
 
-use crate::wake_list::{WakeHandle, WakeList};
+#[cfg(all(
+    feature = "critical_section",
+    not(any(
+        all(feature = "pi_lock", target_os = "linux"),
+        feature = "std_lock",
+        feature = "ticket_lock"
+    ))
+))]
+use crate::cs_lock::CsLock as RawLock;
+#[cfg(all(feature = "pi_lock", target_os = "linux"))]
+use crate::pi_lock::PiLock as RawLock;
+#[cfg(all(
+    feature = "std_lock",
+    not(all(feature = "pi_lock", target_os = "linux"))
+))]
+use crate::std_lock::StdLock as RawLock;
+#[cfg(all(
+    feature = "ticket_lock",
+    not(any(
+        all(feature = "pi_lock", target_os = "linux"),
+        feature = "std_lock"
+    ))
+))]
+use crate::ticket_lock::TicketLock as RawLock;
+use crate::{
+    atomic::{
+        checked::{fence, AtomicBool},
+        Ordering::{Acquire, Relaxed, Release, SeqCst},
+    },
+    wake_list::{WakeHandle, WakeList},
+};
+
+/// Spinlock
+///
+/// Not safe to take from an interrupt handler on a single-core target, since
+/// the handler would spin forever on a lock held by the code it interrupted.
+/// Enable the **`critical_section`** feature for a lock that is.
+#[cfg(not(any(
+    all(feature = "pi_lock", target_os = "linux"),
+    feature = "std_lock",
+    feature = "ticket_lock",
+    feature = "critical_section"
+)))]
+struct SpinLock(AtomicBool);
+
+#[cfg(not(any(
+    all(feature = "pi_lock", target_os = "linux"),
+    feature = "std_lock",
+    feature = "ticket_lock",
+    feature = "critical_section"
+)))]
+impl SpinLock {
+    const_fn! {
+        /// Create a new unlocked spinlock
+        const fn new() -> Self {
+            Self(AtomicBool::new(false))
+        }
+    }
+
+    /// Try to acquire the lock, returning true if acquired
+    ///
+    /// A single `Acquire` compare-exchange when uncontended, paired with the
+    /// `Release` store on unlock.  A failed attempt doesn't write.
+    fn try_lock(&self) -> bool {
+        self.0
+            .compare_exchange(false, true, Acquire, Relaxed)
+            .is_ok()
+    }
+
+    /// Wait until the lock is acquired
+    fn lock(&self) {
+        let mut attempt = 0;
+
+        while !self.try_lock() {
+            // Let loom schedule the holder, rather than exploring each spin
+            #[cfg(loom)]
+            loom::thread::yield_now();
+            crate::backoff::backoff(attempt);
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    /// Release the lock
+    fn unlock(&self) {
+        self.0.store(false, Release);
+    }
+}
+
+#[cfg(not(any(
+    all(feature = "pi_lock", target_os = "linux"),
+    feature = "std_lock",
+    feature = "ticket_lock",
+    feature = "critical_section"
+)))]
+type RawLock = SpinLock;
+
+/// Aligned to its own cache line, so cores contending on the contents don't
+/// also invalidate neighboring data (like `Arc` reference counts)
+///
+/// Modern x86_64 and aarch64 processors prefetch cache lines in pairs.
+#[cfg_attr(
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    repr(align(128))
+)]
+#[cfg_attr(
+    not(any(target_arch = "x86_64", target_arch = "aarch64")),
+    repr(align(64))
+)]
+struct CachePadded<T>(T);
+
+/// Releases the lock when a closure returns, or poisons the data and wakes
+/// every waiting task when unwinding from a panic
+struct Guard<'a, T>(&'a Mutex<T>);
+
+impl<T> Guard<'_, T> {
+    /// Release the lock after the closure returned normally
+    fn unlock(self) {
+        let mutex = self.0;
+
+        mem::forget(self);
+        mutex.unlock();
+    }
+}
+
+impl<T> Drop for Guard<'_, T> {
+    fn drop(&mut self) {
+        // Only reached when unwinding from a panic while locked
+        (self.0.poison)(unsafe { &mut *self.0.data.get() });
+        self.0.unlock();
+        self.0.wake_all();
+    }
+}
+
+/// Poison function that leaves the data unchanged
+fn ignore<T>(_data: &mut T) {}
 
 /// Mutex
+///
+/// If a closure panics while the lock is held, the data is poisoned, the lock
+/// is released, and every waiting task is woken, so nothing waits forever on
+/// a lock that will never be released.
 pub(crate) struct Mutex<T> {
-    /// True if mutex is currently being accessed
-    lock: AtomicBool,
+    /// Lock held while mutex is being accessed
+    lock: CachePadded<RawLock>,
+    /// Set by tasks that registered after failing to take the lock, which
+    /// the holder wakes on unlock
+    contended: AtomicBool,
     /// Data in transit
-    data: UnsafeCell<Option<T>>,
+    data: UnsafeCell<T>,
+    /// Called on the data after a panic while locked
+    poison: fn(&mut T),
     /// List of waiting senders
     send: WakeList,
     /// List of waiting receivers
@@ -25,133 +165,205 @@ unsafe impl<T: Send> Send for Mutex<T> {}
 unsafe impl<T: Send> Sync for Mutex<T> {}
 
 impl<T> Mutex<T> {
-    /// Create a new mutex
-    pub(crate) const fn new() -> Self {
-        let lock = AtomicBool::new(false);
-        let data = UnsafeCell::new(None);
-        let send = WakeList::new();
-        let recv = WakeList::new();
-
-        Self {
-            lock,
-            data,
-            send,
-            recv,
+    const_fn! {
+        /// Create a new mutex
+        pub(crate) const fn new(data: T) -> Self {
+            Self::with_poison(data, ignore)
         }
     }
 
-    /// Try to store data in the mutex
-    pub(crate) fn store(
-        &self,
-        data: &Cell<Option<T>>,
-        cx: &mut Context<'_>,
-        wh: &mut WakeHandle,
-    ) -> Poll<()> {
-        // Try to acquire lock
-        if self.lock.swap(true, Acquire) {
-            // Data is contended, register sender to wake list
-            wh.register(&self.send, cx.waker().clone());
+    const_fn! {
+        /// Create a new mutex, calling `poison` on the data after a panic while
+        /// locked
+        pub(crate) const fn with_poison(data: T, poison: fn(&mut T)) -> Self {
+            let lock = CachePadded(RawLock::new());
+            let contended = AtomicBool::new(false);
+            let data = UnsafeCell::new(data);
+            let send = WakeList::new();
+            let recv = WakeList::new();
 
-            // Try again just in case registration is unnecessary
-            if self.lock.swap(true, Acquire) {
-                // Will be awoken
-                return Poll::Pending;
-            } else {
-                // Locked, and registered
-                // If can't send until receive
-                if unsafe { (*self.data.get()).is_some() } {
-                    // Release lock
-                    self.lock.store(false, Release);
-                    // Wake a receiver
-                    self.recv.wake_one();
-                    return Poll::Pending;
-                }
-
-                // Registration was unnecessary, unregister
-                *wh = WakeHandle::new();
-            }
-        } else {
-            // Locked, but not registered
-            // If can't send until receive, register and return
-            if unsafe { (*self.data.get()).is_some() } {
-                // Register
-                wh.register(&self.send, cx.waker().clone());
-                // Release lock
-                self.lock.store(false, Release);
-                // Wake a receiver
-                self.recv.wake_one();
-                return Poll::Pending;
+            Self {
+                lock,
+                contended,
+                data,
+                poison,
+                send,
+                recv,
             }
         }
+    }
+
+    /// Spin until the lock is acquired, then run `f` on the inner data
+    ///
+    /// Doesn't wake anything, so the caller is responsible for waking.
+    pub(crate) fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        // Acquire lock
+        self.lock.0.lock();
 
-        // Write to inner data
-        unsafe { (*self.data.get()) = data.take() };
+        let guard = Guard(self);
+
+        // Access inner data
+        let ret = f(unsafe { &mut *self.data.get() });
 
         // Release lock
-        self.lock.store(false, Release);
+        guard.unlock();
+
+        ret
+    }
+
+    /// Release the lock, waking every task that registered while it was
+    /// held
+    fn unlock(&self) {
+        self.lock.0.unlock();
+        fence(SeqCst);
 
-        // Wake a receiver
+        if self.contended.load(Relaxed) && self.contended.swap(false, Acquire) {
+            self.wake_all();
+        }
+    }
+
+    /// Wake a receiver
+    pub(crate) fn wake_recv(&self) {
         self.recv.wake_one();
+    }
+
+    /// Wake a sender
+    pub(crate) fn wake_send(&self) {
+        self.send.wake_one();
+    }
+
+    /// Wake all receivers
+    pub(crate) fn wake_all_recv(&self) {
+        self.recv.wake_all();
+    }
+
+    /// Wake all senders
+    pub(crate) fn wake_all_send(&self) {
+        self.send.wake_all();
+    }
 
-        Poll::Ready(())
+    /// Wake all senders and receivers
+    pub(crate) fn wake_all(&self) {
+        self.send.wake_all();
+        self.recv.wake_all();
+    }
+
+    /// Unregister a canceled sender, passing its wakeup on to another sender
+    /// if it was woken but not polled since
+    pub(crate) fn cancel_send(&self, wh: &mut WakeHandle) {
+        if wh.cancel() {
+            self.send.wake_one();
+        }
+    }
+
+    /// Unregister a canceled receiver, passing its wakeup on to another
+    /// receiver if it was woken but not polled since
+    pub(crate) fn cancel_recv(&self, wh: &mut WakeHandle) {
+        if wh.cancel() {
+            self.recv.wake_one();
+        }
+    }
+
+    /// Try to make progress sending, registering on the sender wake list if
+    /// `f` returns `Pending`
+    pub(crate) fn poll_send<R>(
+        &self,
+        cx: &mut Context<'_>,
+        wh: &mut WakeHandle,
+        f: impl FnOnce(&mut T) -> Poll<R>,
+    ) -> Poll<R> {
+        self.poll(&self.send, &self.recv, cx, wh, f)
     }
 
-    /// Try to take data from the mutex
-    pub(crate) fn take(
+    /// Try to make progress receiving, registering on the receiver wake list
+    /// if `f` returns `Pending`
+    pub(crate) fn poll_recv<R>(
         &self,
         cx: &mut Context<'_>,
         wh: &mut WakeHandle,
-    ) -> Poll<T> {
+        f: impl FnOnce(&mut T) -> Poll<R>,
+    ) -> Poll<R> {
+        self.poll(&self.recv, &self.send, cx, wh, f)
+    }
+
+    fn poll<R>(
+        &self,
+        wait: &WakeList,
+        wake: &WakeList,
+        cx: &mut Context<'_>,
+        wh: &mut WakeHandle,
+        f: impl FnOnce(&mut T) -> Poll<R>,
+    ) -> Poll<R> {
         // Try to acquire lock
-        if self.lock.swap(true, Acquire) {
-            // Data is contended, register sender to wake list
-            wh.register(&self.recv, cx.waker().clone());
+        if !self.lock.0.try_lock() {
+            #[cfg(feature = "test_util")]
+            crate::test_util::yield_point();
+
+            // Data is contended, register to wake list
+            wh.register(wait, cx.waker());
+            // Ask the holder to wake this task on unlock, whether or not it
+            // would otherwise wake this side.  Either the retry sees the
+            // release, or the holder sees the flag, pairing with `unlock()`.
+            self.contended.fetch_or(true, Release);
+            fence(SeqCst);
 
             // Try again just in case registration is unnecessary
-            if self.lock.swap(true, Acquire) {
-                // Will be awoken
+            if !self.lock.0.try_lock() {
+                // Will be awoken, so let loom run the holder
+                #[cfg(loom)]
+                loom::thread::yield_now();
                 return Poll::Pending;
-            } else {
-                // Locked, and registered
-                // If can't receive until send
-                if unsafe { (*self.data.get()).is_none() } {
-                    // Release lock
-                    self.lock.store(false, Release);
-                    // Wake a sender
-                    self.send.wake_one();
-                    return Poll::Pending;
-                }
-
-                // Registration was unnecessary, unregister
-                *wh = WakeHandle::new();
             }
-        } else {
-            // If can't receive until send, register and return
-            if unsafe { (*self.data.get()).is_none() } {
-                // Register
-                wh.register(&self.recv, cx.waker().clone());
-                // Release lock
-                self.lock.store(false, Release);
-                // Wake a sender
-                self.send.wake_one();
-                return Poll::Pending;
+
+            let guard = Guard(self);
+
+            // Locked, and registered
+            let ret = f(unsafe { &mut *self.data.get() });
+
+            // Registration was unnecessary, unregister
+            if ret.is_ready() {
+                *wh = WakeHandle::new();
             }
+
+            // Release lock
+            guard.unlock();
+            // Wake the other side
+            wake.wake_one();
+
+            return ret;
         }
 
-        // Take from inner data
-        let ret = if let Some(data) = unsafe { (*self.data.get()).take() } {
-            Poll::Ready(data)
+        let guard = Guard(self);
+
+        // Locked, but not registered
+        let ret = f(unsafe { &mut *self.data.get() });
+
+        // If can't make progress, register, otherwise unregister so that a
+        // wakeup that led here isn't passed on later
+        if ret.is_pending() {
+            // Runs under the lock, so hooked operations on this mutex are
+            // contended rather than landing before registration
+            #[cfg(feature = "test_util")]
+            crate::test_util::yield_point();
+
+            wh.register(wait, cx.waker());
         } else {
-            Poll::Pending
-        };
+            *wh = WakeHandle::new();
+        }
 
         // Release lock
-        self.lock.store(false, Release);
+        guard.unlock();
+        // Wake the other side
+        wake.wake_one();
 
-        // Wake a sender
-        self.send.wake_one();
+        // Registering while this task's previous wakeup is still in progress
+        // wakes it again immediately, so let loom run the waking thread
+        // rather than exploring each poll while it's preempted
+        #[cfg(loom)]
+        if ret.is_pending() {
+            loom::thread::yield_now();
+        }
 
-        // Return `Ready(_)`
         ret
     }
 }
@@ -0,0 +1,286 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{
+    future,
+    task::{Context, Poll},
+};
+
+use crate::{
+    atomic::Arc, merge::poll_each, mutex::Mutex, Channel, Receiver, SendError,
+    TrySendError,
+};
+
+/// Routing table protected by the mutex
+struct Locked<K, T> {
+    /// Channel for each key
+    channels: BTreeMap<K, Channel<T>>,
+    /// Capacity of newly created channels
+    capacity: usize,
+}
+
+impl<K: Ord, T> Locked<K, T> {
+    /// Get a handle to the channel for a key, creating it if missing
+    fn channel(&mut self, key: K) -> Channel<T> {
+        let capacity = self.capacity;
+
+        self.channels
+            .entry(key)
+            .or_insert_with(|| Channel::with_capacity(capacity))
+            .clone()
+    }
+}
+
+/// Routes keyed messages to a channel for each key
+///
+/// The channel for a key is created by the first message sent to it or the
+/// first subscription, whichever comes first, so messages sent before
+/// subscribing are kept until received.  Clones share the same routing
+/// table.
+///
+/// ```rust
+/// use whisk::{Mux, RecvError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let connections = Mux::with_capacity(4);
+///
+///         connections.send(7, "hello").await.unwrap();
+///         connections.send(9, "hi").await.unwrap();
+///         connections.send(7, "bye").await.unwrap();
+///
+///         let seven = connections.subscribe(7);
+///
+///         assert_eq!(seven.recv().await, Ok("hello"));
+///         assert_eq!(seven.recv().await, Ok("bye"));
+///         assert!(connections.unsubscribe(&7));
+///         assert_eq!(seven.recv().await, Err(RecvError));
+///         assert_eq!(connections.subscribe(9).recv().await, Ok("hi"));
+///     });
+/// }
+/// ```
+pub struct Mux<K, T>(Arc<Mutex<Locked<K, T>>>);
+
+impl<K: Ord, T> Mux<K, T> {
+    /// Create a new mux with no keys, creating channels with a capacity of
+    /// one message.
+    pub fn new() -> Self {
+        Self::with_capacity(1)
+    }
+
+    /// Create a new mux with no keys, creating channels that can hold
+    /// `capacity` messages.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(Locked {
+            channels: BTreeMap::new(),
+            capacity,
+        })))
+    }
+
+    /// Send a message to the channel for `key`, creating it if missing.
+    ///
+    /// Returns an error if the key is unsubscribed while waiting for room.
+    pub async fn send(&self, key: K, message: T) -> Result<(), SendError<T>> {
+        let channel = self.0.with(|locked| locked.channel(key));
+
+        channel.send(message).await
+    }
+
+    /// Send a message to the channel for `key` if there is room, creating
+    /// it if missing, without waiting.
+    pub fn try_send(&self, key: K, message: T) -> Result<(), TrySendError<T>> {
+        let channel = self.0.with(|locked| locked.channel(key));
+
+        channel.try_send(message)
+    }
+
+    /// Get a receiver for the channel for `key`, creating it if missing.
+    ///
+    /// Subscribing more than once to the same key returns receivers that
+    /// share its messages.
+    pub fn subscribe(&self, key: K) -> Receiver<T> {
+        self.0.with(|locked| locked.channel(key)).into_receiver()
+    }
+
+    /// Remove the channel for `key` and close it, so its receivers stop
+    /// receiving, even messages that were already sent.
+    ///
+    /// Returns false if there was no channel for `key`.
+    pub fn unsubscribe(&self, key: &K) -> bool {
+        let Some(channel) = self.0.with(|locked| locked.channels.remove(key))
+        else {
+            return false;
+        };
+
+        channel.close();
+        true
+    }
+
+    /// Return true if there is a channel for `key`.
+    pub fn contains(&self, key: &K) -> bool {
+        self.0.with(|locked| locked.channels.contains_key(key))
+    }
+
+    /// Get the number of keys with a channel.
+    pub fn len(&self) -> usize {
+        self.0.with(|locked| locked.channels.len())
+    }
+
+    /// Return true if no keys have a channel.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, T> Clone for Mux<K, T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<K: Ord, T> Default for Mux<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, T> core::fmt::Debug for Mux<K, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Mux").finish_non_exhaustive()
+    }
+}
+
+/// Combines per-key channels into one stream of keyed messages
+///
+/// The complement of [`Mux`].  Messages from each channel are received in
+/// the order they were sent, tagged with the channel's key, while channels
+/// are polled in turn so a busy key can't starve the others.  Once a
+/// channel is closed or disconnected, its key is removed.  Enable the
+/// **`futures_core_3`** feature for `Demux` to implement
+/// [`Stream`](futures_core_3::Stream).
+///
+/// ```rust
+/// use whisk::{Channel, Demux};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let a = Channel::with_capacity(2);
+///         let b = Channel::with_capacity(2);
+///         let mut demux = Demux::new();
+///
+///         demux.insert('a', a.clone());
+///         demux.insert('b', b.clone());
+///         a.send(1).await.unwrap();
+///         b.send(1).await.unwrap();
+///         a.send(2).await.unwrap();
+///         a.seal();
+///         b.seal();
+///
+///         let mut received = Vec::new();
+///
+///         while let Some(message) = demux.recv().await {
+///             received.push(message);
+///         }
+///
+///         assert_eq!(received, [('a', 1), ('b', 1), ('a', 2)]);
+///     });
+/// }
+/// ```
+pub struct Demux<K, T, U: ?Sized = ()> {
+    channels: Vec<(K, Channel<T, U>)>,
+    /// Index of the channel to poll first
+    next: usize,
+}
+
+impl<K: Clone, T, U: ?Sized> Demux<K, T, U> {
+    /// Create a new demux with no channels.
+    pub fn new() -> Self {
+        Self {
+            channels: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// Add a channel to receive from, tagging its messages with `key`.
+    pub fn insert(&mut self, key: K, channel: Channel<T, U>) {
+        self.channels.push((key, channel));
+    }
+
+    /// Get the number of channels still open.
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    /// Return true if every channel has been closed or disconnected.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Receive a message from any channel, with its key.
+    ///
+    /// Returns `None` once every channel is closed or disconnected.
+    pub async fn recv(&mut self) -> Option<(K, T)> {
+        future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Poll for a message from any channel, registering the waker of `cx`
+    /// with each channel if none is available.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<(K, T)>> {
+        let channels = &mut self.channels;
+
+        poll_each(channels, &mut self.next, cx, |(_, channel)| channel).map(
+            |received| {
+                received.map(|(index, message)| {
+                    (channels[index].0.clone(), message)
+                })
+            },
+        )
+    }
+}
+
+impl<K: Clone, T, U: ?Sized> Default for Demux<K, T, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, T, U: ?Sized> FromIterator<(K, Channel<T, U>)>
+    for Demux<K, T, U>
+{
+    fn from_iter<I: IntoIterator<Item = (K, Channel<T, U>)>>(iter: I) -> Self {
+        Self {
+            channels: iter.into_iter().collect(),
+            next: 0,
+        }
+    }
+}
+
+// Keys are never pinned
+impl<K, T, U: ?Sized> Unpin for Demux<K, T, U> {}
+
+impl<K, T, U: ?Sized> core::fmt::Debug for Demux<K, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Demux")
+            .field("len", &self.channels.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "futures_core_3")]
+impl<K: Clone, T, U: ?Sized> futures_core_3::Stream for Demux<K, T, U> {
+    type Item = (K, T);
+
+    fn poll_next(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(K, T)>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+#[cfg(feature = "futures_core_3")]
+impl<K: Clone, T, U: ?Sized> futures_core_3::FusedStream for Demux<K, T, U> {
+    fn is_terminated(&self) -> bool {
+        self.is_empty()
+    }
+}
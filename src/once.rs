@@ -0,0 +1,140 @@
+use core::{cell::UnsafeCell, future, mem::MaybeUninit, task::Poll};
+
+use crate::{
+    atomic::{
+        AtomicU8,
+        Ordering::{AcqRel, Acquire, Release},
+    },
+    wake_list::{WakeHandle, WakeList},
+};
+
+/// State before the value is set
+const EMPTY: u8 = 0;
+/// State while the value is being written
+const SETTING: u8 = 1;
+/// State once the value has been written
+const SET: u8 = 2;
+
+/// A cell set once by one task, and waited on by any number of tasks
+///
+/// The async equivalent of a `OnceLock`, useful for waiting for
+/// configuration or a handle to be initialized.
+///
+/// ```rust
+/// use whisk::Once;
+///
+/// async fn worker(config: &Once<u32>) -> u32 {
+///     *config.get().await * 2
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let config = Once::new();
+///         let initializing = async {
+///             assert_eq!(config.try_get(), None);
+///             config.set(21).unwrap();
+///         };
+///         let (a, b, ()) =
+///             futures::join!(worker(&config), worker(&config), initializing);
+///
+///         assert_eq!((a, b), (42, 42));
+///         assert_eq!(config.set(0), Err(0));
+///     });
+/// }
+/// ```
+pub struct Once<T> {
+    /// Whether the value is set
+    state: AtomicU8,
+    /// Value, initialized once the state is `SET`
+    value: UnsafeCell<MaybeUninit<T>>,
+    /// List of waiting tasks
+    waiters: WakeList,
+}
+
+unsafe impl<T: Send> Send for Once<T> {}
+unsafe impl<T: Send + Sync> Sync for Once<T> {}
+
+impl<T> Once<T> {
+    const_fn! {
+        /// Create a new, unset cell.
+        pub const fn new() -> Self {
+            Self {
+                state: AtomicU8::new(EMPTY),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+                waiters: WakeList::new(),
+            }
+        }
+    }
+
+    /// Set the value, waking every waiting task.
+    ///
+    /// Returns the value back as an error if already set.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self
+            .state
+            .compare_exchange(EMPTY, SETTING, AcqRel, Acquire)
+            .is_err()
+        {
+            return Err(value);
+        }
+
+        // Safe because only the task that claimed the cell writes it, and
+        // nothing reads it until the state is `SET`
+        unsafe { (*self.value.get()).write(value) };
+        self.state.store(SET, Release);
+        self.waiters.wake_all();
+
+        Ok(())
+    }
+
+    /// Get the value if set, without waiting.
+    pub fn try_get(&self) -> Option<&T> {
+        if self.state.load(Acquire) != SET {
+            return None;
+        }
+
+        // Safe because the value is never written again once set
+        Some(unsafe { (*self.value.get()).assume_init_ref() })
+    }
+
+    /// Wait until the value is set, then get it.
+    pub async fn get(&self) -> &T {
+        let mut wh = WakeHandle::new();
+
+        future::poll_fn(|cx| {
+            if let Some(value) = self.try_get() {
+                return Poll::Ready(value);
+            }
+
+            wh.register(&self.waiters, cx.waker());
+
+            // Check again in case of setting before registering
+            self.try_get().map_or(Poll::Pending, Poll::Ready)
+        })
+        .await
+    }
+}
+
+impl<T> Default for Once<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Once<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == SET {
+            // Safe because the value was initialized when set
+            unsafe { self.value.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Once<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Once")
+            .field("set", &self.try_get().is_some())
+            .finish_non_exhaustive()
+    }
+}
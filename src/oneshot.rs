@@ -0,0 +1,218 @@
+use core::{
+    cell::UnsafeCell,
+    future,
+    mem::MaybeUninit,
+    task::{Poll, Waker},
+};
+
+use crate::{
+    atomic::{
+        Arc, AtomicU8,
+        Ordering::{AcqRel, Acquire},
+    },
+    mutex::Mutex,
+    RecvError, SendError,
+};
+
+/// State bit set once the message has been written, and cleared once taken
+const MESSAGE: u8 = 0b001;
+/// State bit set once either half has been dropped
+const CLOSED: u8 = 0b010;
+/// State bit set once the receiver has stored a waker
+const WAITING: u8 = 0b100;
+
+/// State shared between the two halves
+struct Shared<T> {
+    /// Bitflags for the state of the message slot
+    state: AtomicU8,
+    /// Message, initialized while the `MESSAGE` bit is set
+    message: UnsafeCell<MaybeUninit<T>>,
+    /// Waker of the waiting receiver
+    waker: Mutex<Option<Waker>>,
+}
+
+unsafe impl<T: Send> Send for Shared<T> {}
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+impl<T> Shared<T> {
+    /// Take the message if sent, or an error if the sender was dropped
+    fn take(&self) -> Option<Result<T, RecvError>> {
+        let state = self.state.load(Acquire);
+
+        if state & MESSAGE != 0 {
+            // Safe because the sender finished writing before setting the
+            // bit, and clearing it stops the receiver's drop from reading it
+            let message = unsafe { (*self.message.get()).assume_init_read() };
+
+            self.state.fetch_and(!MESSAGE, AcqRel);
+            return Some(Ok(message));
+        }
+
+        (state & CLOSED != 0).then_some(Err(RecvError))
+    }
+
+    /// Wake the receiver, if it stored a waker
+    fn wake(&self) {
+        if let Some(waker) = self.waker.with(Option::take) {
+            waker.wake();
+        }
+    }
+}
+
+/// Receiving half of a channel for exactly one message
+///
+/// Both halves are consumed when used, so the type system enforces that only
+/// one message is sent.  With a single possible receiver, a oneshot stores
+/// one waker rather than a wake list.  The message itself is handed off
+/// through an atomic slot without locking, so the lock is only taken when the
+/// receiver has to wait.
+///
+/// ```rust
+/// use whisk::{Channel, Oneshot, OneshotSender, RecvError};
+///
+/// enum Cmd {
+///     /// Tell messenger to add
+///     Add(u32, u32, OneshotSender<u32>),
+/// }
+///
+/// async fn worker_main(commands: Channel<Cmd>) {
+///     while let Ok(command) = commands.recv().await {
+///         match command {
+///             Cmd::Add(a, b, s) => s.send(a + b).unwrap(),
+///         }
+///     }
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let worker = worker_main(channel.clone());
+///         let tasker = async {
+///             let (oneshot, reply) = Oneshot::new();
+///
+///             channel.send(Cmd::Add(43, 400, reply)).await.unwrap();
+///             assert_eq!(oneshot.recv().await, Ok(443));
+///
+///             // Dropping the sender without sending is an error
+///             let (oneshot, reply) = Oneshot::<u32>::new();
+///
+///             drop(reply);
+///             assert_eq!(oneshot.recv().await, Err(RecvError));
+///             channel.close();
+///         };
+///
+///         futures::future::join(worker, tasker).await;
+///     });
+/// }
+/// ```
+pub struct Oneshot<T>(Arc<Shared<T>>);
+
+impl<T> Oneshot<T> {
+    /// Create a new oneshot, returning the receiving and sending halves.
+    pub fn new() -> (Self, OneshotSender<T>) {
+        let shared = Arc::new(Shared {
+            state: AtomicU8::new(0),
+            message: UnsafeCell::new(MaybeUninit::uninit()),
+            waker: Mutex::new(None),
+        });
+
+        (Self(shared.clone()), OneshotSender(shared))
+    }
+
+    /// Receive the message, consuming this receiver.
+    ///
+    /// Returns an error if the sender was dropped without sending.
+    pub async fn recv(self) -> Result<T, RecvError> {
+        future::poll_fn(|cx| {
+            if let Some(result) = self.0.take() {
+                return Poll::Ready(result);
+            }
+
+            self.0.waker.with(|waker| {
+                if !waker.as_ref().is_some_and(|w| w.will_wake(cx.waker())) {
+                    *waker = Some(cx.waker().clone());
+                }
+            });
+
+            self.0.state.fetch_or(WAITING, AcqRel);
+
+            // Check again, in case the sender finished before seeing the bit
+            self.0.take().map_or(Poll::Pending, Poll::Ready)
+        })
+        .await
+    }
+}
+
+impl<T> Drop for Oneshot<T> {
+    fn drop(&mut self) {
+        let state = self.0.state.fetch_or(CLOSED, AcqRel);
+
+        // Drop an unreceived message
+        if state & MESSAGE != 0 {
+            // Safe because the sender finished writing before setting the bit
+            unsafe { (*self.0.message.get()).assume_init_drop() };
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Oneshot<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Oneshot").finish_non_exhaustive()
+    }
+}
+
+/// Sending half of a [`Oneshot`]
+pub struct OneshotSender<T>(Arc<Shared<T>>);
+
+impl<T> OneshotSender<T> {
+    /// Send the message, consuming this sender.
+    ///
+    /// Returns the message back as an error if the receiver was dropped.
+    pub fn send(self, message: T) -> Result<(), SendError<T>> {
+        if self.is_closed() {
+            return Err(SendError(message));
+        }
+
+        // Safe because the receiver doesn't read until the bit is set
+        unsafe { (*self.0.message.get()).write(message) };
+
+        let state = self.0.state.fetch_or(MESSAGE, AcqRel);
+
+        if state & CLOSED != 0 {
+            // Safe because the receiver was dropped before the bit was set,
+            // so it won't read the message
+            let message = unsafe { (*self.0.message.get()).assume_init_read() };
+
+            return Err(SendError(message));
+        }
+
+        if state & WAITING != 0 {
+            self.0.wake();
+        }
+
+        Ok(())
+    }
+
+    /// Check if the receiver was dropped.
+    pub fn is_closed(&self) -> bool {
+        self.0.state.load(Acquire) & CLOSED != 0
+    }
+}
+
+impl<T> Drop for OneshotSender<T> {
+    fn drop(&mut self) {
+        let state = self.0.state.fetch_or(CLOSED, AcqRel);
+
+        // A sent message already woke the receiver
+        if state & (MESSAGE | WAITING) == WAITING {
+            self.0.wake();
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for OneshotSender<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OneshotSender").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,60 @@
+/// What sending does when a channel is full
+///
+/// Selected at construction with
+/// [`Channel::with_policy()`](crate::Channel::with_policy) or
+/// [`Queue::with_policy()`](crate::Queue::with_policy).
+///
+/// ```rust
+/// use futures::poll;
+/// use whisk::{Channel, OverflowPolicy, TrySendError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let oldest = Channel::with_policy(2, OverflowPolicy::DropOldest);
+///         let newest = Channel::with_policy(2, OverflowPolicy::DropNewest);
+///         let fail = Channel::with_policy(2, OverflowPolicy::Fail);
+///
+///         for i in 0..3 {
+///             oldest.send(i).await.unwrap();
+///             newest.send(i).await.unwrap();
+///         }
+///
+///         let mut kept = Vec::new();
+///
+///         oldest.recv_many(&mut kept, 3).await;
+///         assert_eq!(kept, [1, 2]);
+///         assert_eq!((newest.recv().await, newest.recv().await), (Ok(0), Ok(1)));
+///
+///         fail.send(0).await.unwrap();
+///         fail.send(1).await.unwrap();
+///         assert_eq!(fail.try_send(2), Err(TrySendError::Full(2)));
+///         assert!(poll!(fail.send(2)).is_pending());
+///     });
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait for a receive to free a slot
+    #[default]
+    Block,
+    /// Drop the oldest message in transit to make room, so sending never
+    /// waits
+    DropOldest,
+    /// Drop the message being sent, so sending never waits
+    DropNewest,
+    /// Fail to send without waiting, returning the message in an error
+    ///
+    /// Only affects trying to send, which returns
+    /// [`TrySendError::Full`](crate::TrySendError::Full), since a
+    /// [`SendError`](crate::SendError) means the channel is closed.  Sending
+    /// waits for a free slot, as with [`OverflowPolicy::Block`].
+    Fail,
+}
+
+impl OverflowPolicy {
+    /// Return true if sending waits for a free slot when full
+    pub(crate) fn waits(self) -> bool {
+        matches!(self, Self::Block | Self::Fail)
+    }
+}
@@ -0,0 +1,86 @@
+use alloc::boxed::Box;
+use core::{
+    mem::MaybeUninit,
+    ptr,
+    sync::atomic::{
+        AtomicPtr,
+        Ordering::{AcqRel, Acquire},
+    },
+};
+
+/// Blocking lock with priority inheritance
+///
+/// The pthread mutex is allocated on first use, since it can't be moved once
+/// initialized.
+pub(crate) struct PiLock(AtomicPtr<libc::pthread_mutex_t>);
+
+impl PiLock {
+    /// Create a new unlocked lock
+    pub(crate) const fn new() -> Self {
+        Self(AtomicPtr::new(ptr::null_mut()))
+    }
+
+    /// Get the pthread mutex, initializing it if needed
+    fn raw(&self) -> *mut libc::pthread_mutex_t {
+        let raw = self.0.load(Acquire);
+
+        if !raw.is_null() {
+            return raw;
+        }
+
+        let new = Box::into_raw(Box::new(libc::PTHREAD_MUTEX_INITIALIZER));
+
+        unsafe {
+            let mut attr = MaybeUninit::uninit();
+
+            libc::pthread_mutexattr_init(attr.as_mut_ptr());
+            libc::pthread_mutexattr_setprotocol(
+                attr.as_mut_ptr(),
+                libc::PTHREAD_PRIO_INHERIT,
+            );
+            libc::pthread_mutex_init(new, attr.as_ptr());
+            libc::pthread_mutexattr_destroy(attr.as_mut_ptr());
+        }
+
+        match self.0.compare_exchange(raw, new, AcqRel, Acquire) {
+            Ok(_) => new,
+            Err(raw) => {
+                // Another thread initialized first
+                unsafe {
+                    libc::pthread_mutex_destroy(new);
+                    drop(Box::from_raw(new));
+                }
+
+                raw
+            }
+        }
+    }
+
+    /// Try to acquire the lock, returning true if acquired
+    pub(crate) fn try_lock(&self) -> bool {
+        unsafe { libc::pthread_mutex_trylock(self.raw()) == 0 }
+    }
+
+    /// Block until the lock is acquired
+    pub(crate) fn lock(&self) {
+        unsafe { libc::pthread_mutex_lock(self.raw()) };
+    }
+
+    /// Release the lock
+    pub(crate) fn unlock(&self) {
+        unsafe { libc::pthread_mutex_unlock(self.raw()) };
+    }
+}
+
+impl Drop for PiLock {
+    fn drop(&mut self) {
+        let raw = *self.0.get_mut();
+
+        if !raw.is_null() {
+            unsafe {
+                libc::pthread_mutex_destroy(raw);
+                drop(Box::from_raw(raw));
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use alloc::vec::Vec;
+
+use crate::Channel;
+
+/// Forward every message received from `source` to each of `outputs`,
+/// cloning it for all but the last, until `source` is closed or disconnected.
+///
+/// Each message is sent to the outputs in order, so the slowest output sets
+/// the pace.  Outputs that are closed are skipped from then on, and if every
+/// output is closed, this returns without receiving any more messages.  When
+/// `source` ends, the outputs are sealed, so their receivers get the
+/// messages already forwarded before [`RecvError`](crate::RecvError).
+///
+/// ```rust
+/// use whisk::Channel;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let source = Channel::new();
+///         let outputs = [Channel::new(), Channel::new()];
+///         let sending = async {
+///             source.send("started").await.unwrap();
+///             source.seal();
+///         };
+///         let log = async {
+///             let mut lines = Vec::new();
+///
+///             while let Ok(line) = outputs[1].recv().await {
+///                 lines.push(line);
+///             }
+///
+///             lines
+///         };
+///         let ((), (), started, log) = futures::join!(
+///             sending,
+///             whisk::tee(&source, &outputs),
+///             outputs[0].recv(),
+///             log,
+///         );
+///
+///         assert_eq!(started, Ok("started"));
+///         assert_eq!(log, ["started"]);
+///     });
+/// }
+/// ```
+pub async fn tee<T: Clone, U: ?Sized, V: ?Sized>(
+    source: &Channel<T, U>,
+    outputs: &[Channel<T, V>],
+) {
+    let mut open: Vec<&Channel<T, V>> = outputs.iter().collect();
+
+    while !open.is_empty() {
+        let Ok(message) = source.recv().await else {
+            break;
+        };
+        let mut message = Some(message);
+        let mut index = 0;
+
+        while let Some(output) = open.get(index) {
+            // The last output takes the message, the others get clones
+            let message = if index + 1 == open.len() {
+                message.take()
+            } else {
+                message.clone()
+            };
+            let Some(message) = message else {
+                break;
+            };
+
+            if output.send(message).await.is_err() {
+                open.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    for output in outputs {
+        output.seal();
+    }
+}
+
+/// Forward every message received from `source` to `destination`, until
+/// either is closed.
+///
+/// Closure is propagated: when `source` is closed or disconnected,
+/// `destination` is sealed, so its receivers get the messages already
+/// forwarded before [`RecvError`](crate::RecvError), and when `destination`
+/// is closed, `source` is closed too, so its senders stop.
+///
+/// ```rust
+/// use whisk::Channel;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let internal = Channel::new();
+///         let external = Channel::new();
+///         let sending = async {
+///             internal.send(1).await.unwrap();
+///             internal.send(2).await.unwrap();
+///             internal.seal();
+///         };
+///         let receiving = async {
+///             let mut received = Vec::new();
+///
+///             while let Ok(message) = external.recv().await {
+///                 received.push(message);
+///             }
+///
+///             received
+///         };
+///         let ((), (), received) = futures::join!(
+///             sending,
+///             whisk::forward(&internal, &external),
+///             receiving,
+///         );
+///
+///         assert_eq!(received, [1, 2]);
+///     });
+/// }
+/// ```
+pub async fn forward<T, U: ?Sized, V: ?Sized>(
+    source: &Channel<T, U>,
+    destination: &Channel<T, V>,
+) {
+    while let Ok(message) = source.recv().await {
+        if destination.send(message).await.is_err() {
+            source.close();
+            return;
+        }
+    }
+
+    destination.seal();
+}
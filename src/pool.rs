@@ -0,0 +1,181 @@
+//! Worker pools for distributing jobs
+//!
+//! A [`Pool`] fans jobs out from one ingress channel to a fixed number of
+//! worker channels, and collects what the workers send back on one results
+//! channel.
+//!
+//! ```rust
+//! use whisk::pool::{Pool, Strategy, Worker};
+//!
+//! async fn square(worker: Worker<u32, u32>) {
+//!     while let Ok(job) = worker.recv().await {
+//!         worker.send(job * job).await.unwrap();
+//!     }
+//! }
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let pool = Pool::new(2, Strategy::RoundRobin);
+//!         let (ingress, results) = (pool.ingress(), pool.results());
+//!         let workers = (square(pool.worker(0)), square(pool.worker(1)));
+//!         let tasker = async move {
+//!             for job in 1..=4 {
+//!                 ingress.send(job).await.unwrap();
+//!             }
+//!         };
+//!         let collector = async {
+//!             let mut squares = Vec::new();
+//!
+//!             while let Ok(square) = results.recv().await {
+//!                 squares.push(square);
+//!             }
+//!
+//!             squares.sort();
+//!             squares
+//!         };
+//!         let (.., squares) = futures::join!(
+//!             pool.dispatch(),
+//!             workers.0,
+//!             workers.1,
+//!             tasker,
+//!             collector,
+//!         );
+//!
+//!         assert_eq!(squares, [1, 4, 9, 16]);
+//!     });
+//! }
+//! ```
+
+use alloc::boxed::Box;
+
+use crate::{Channel, Receiver, RecvError, SendError, Sender};
+
+/// How a [`Pool`] chooses the worker for each job
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Send to each worker in turn
+    #[default]
+    RoundRobin,
+    /// Send to the worker with the fewest jobs waiting
+    LeastLoaded,
+}
+
+/// Jobs of type `J` distributed between workers sending results of type `R`
+pub struct Pool<J, R> {
+    ingress: Channel<J>,
+    workers: Box<[Channel<J>]>,
+    results: Channel<R>,
+    strategy: Strategy,
+}
+
+impl<J, R> Pool<J, R> {
+    /// Create a new pool with the specified number of workers.
+    ///
+    /// # Panics
+    ///
+    /// If `workers` is zero.
+    pub fn new(workers: usize, strategy: Strategy) -> Self {
+        assert_ne!(workers, 0, "Pool must have at least one worker");
+
+        Self {
+            ingress: Channel::new(),
+            workers: (0..workers).map(|_| Channel::new()).collect(),
+            results: Channel::new(),
+            strategy,
+        }
+    }
+
+    /// Get the number of workers in this pool.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Get a new handle for submitting jobs.
+    pub fn ingress(&self) -> Sender<J> {
+        self.ingress.clone().into_sender()
+    }
+
+    /// Get a new handle for receiving results.
+    ///
+    /// Receiving returns [`RecvError`] once the pool has stopped
+    /// dispatching, and every worker handle is dropped.
+    pub fn results(&self) -> Receiver<R> {
+        self.results.clone().into_receiver()
+    }
+
+    /// Get a new handle for the worker at `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn worker(&self, index: usize) -> Worker<J, R> {
+        Worker {
+            jobs: self.workers[index].clone(),
+            results: self.results.clone(),
+        }
+    }
+
+    /// Distribute jobs to the workers until every ingress handle is dropped,
+    /// then let the workers finish the jobs they have.
+    pub async fn dispatch(self) {
+        let mut next = 0;
+
+        while let Ok(job) = self.ingress.recv().await {
+            let index = match self.strategy {
+                Strategy::RoundRobin => {
+                    let index = next;
+
+                    next = (next + 1) % self.workers.len();
+                    index
+                }
+                Strategy::LeastLoaded => (0..self.workers.len())
+                    .min_by_key(|&i| self.workers[i].len())
+                    .unwrap_or_default(),
+            };
+
+            // A closed worker channel drops its job
+            let _ = self.workers[index].send(job).await;
+        }
+
+        for worker in self.workers.iter() {
+            worker.seal();
+        }
+    }
+}
+
+impl<J, R> core::fmt::Debug for Pool<J, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Pool")
+            .field("workers", &self.workers.len())
+            .field("strategy", &self.strategy)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Handle for one worker of a [`Pool`]
+pub struct Worker<J, R> {
+    jobs: Channel<J>,
+    results: Channel<R>,
+}
+
+impl<J, R> Worker<J, R> {
+    /// Receive the next job for this worker.
+    ///
+    /// Returns an error once the pool has stopped dispatching and no jobs
+    /// remain.
+    pub async fn recv(&self) -> Result<J, RecvError> {
+        self.jobs.recv().await
+    }
+
+    /// Send a result.
+    pub async fn send(&self, result: R) -> Result<(), SendError<R>> {
+        self.results.send(result).await
+    }
+}
+
+impl<J, R> core::fmt::Debug for Worker<J, R> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Worker").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,87 @@
+//! Two-lane channels for handling control messages ahead of bulk data
+//!
+//! A [`Channel`] is a [`lanes::Channel`] with a high and a low priority
+//! lane.  Receiving always drains high priority messages first, preserving
+//! send order within each lane.
+//!
+//! ```rust
+//! use whisk::priority::Channel;
+//!
+//! enum Msg {
+//!     Data(u32),
+//!     Shutdown,
+//! }
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let mailbox = Channel::new();
+//!         let mut processed = 0;
+//!
+//!         mailbox.send_low(Msg::Data(1));
+//!         mailbox.send_low(Msg::Data(2));
+//!         mailbox.send_high(Msg::Shutdown);
+//!
+//!         loop {
+//!             match mailbox.recv().await {
+//!                 Msg::Data(_) => processed += 1,
+//!                 Msg::Shutdown => break,
+//!             }
+//!         }
+//!
+//!         assert_eq!(processed, 0);
+//!     });
+//! }
+//! ```
+
+use crate::lanes;
+
+/// Lane of high priority messages
+const HIGH: usize = 0;
+/// Lane of low priority messages
+const LOW: usize = 1;
+
+/// An MPMC channel with a high and a low priority lane
+///
+/// Lanes are unbounded, so sending never waits.
+pub struct Channel<T>(lanes::Channel<T>);
+
+impl<T> Channel<T> {
+    /// Create a new priority channel.
+    pub fn new() -> Self {
+        Self(lanes::Channel::new(2))
+    }
+
+    /// Send a message that is received before any low priority messages.
+    pub fn send_high(&self, message: T) {
+        self.0.send_lane(HIGH, message);
+    }
+
+    /// Send a message that is received after all high priority messages.
+    pub fn send_low(&self, message: T) {
+        self.0.send_lane(LOW, message);
+    }
+
+    /// Receive the highest priority message from this channel.
+    pub async fn recv(&self) -> T {
+        self.0.recv().await
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Default for Channel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> core::fmt::Debug for Channel<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
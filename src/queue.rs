@@ -1,19 +1,184 @@
+use alloc::{collections::VecDeque, vec::Vec};
 use core::{
-    cell::Cell,
     future::{self, Future},
-    pin::Pin,
+    pin::{pin, Pin},
     task::{Context, Poll},
+    time::Duration,
 };
 
-use crate::{mutex::Mutex, wake_list::WakeHandle};
+use crate::{
+    mutex::Mutex, wake_list::WakeHandle, CancelToken, OverflowPolicy, Permit,
+    Reaper, RecvError, RecvTimeoutError, Reservation, SendError,
+    SendTimeoutError, Sleep, TryRecvError, TrySendError,
+};
+
+/// What a channel handle can do, for tracking disconnection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Role {
+    /// A [`Channel`](crate::Channel), which can send and receive
+    Both,
+    /// A [`Sender`](crate::Sender), which can only send
+    Send,
+    /// A [`Receiver`](crate::Receiver), which can only receive
+    Recv,
+}
+
+/// Data protected by the mutex
+///
+/// Sending and receiving always lock, rather than taking a lock-free slot
+/// for a single producer and consumer, since capacity, reservations, the
+/// overflow policy, closing, and disconnection are all checked together with
+/// the message, which one atomic state can't cover.  Uncontended, locking is
+/// a compare-exchange and a store, and contended spinning backs off, or is
+/// replaced by blocking with the **`std_lock`** feature.  Only
+/// [`Oneshot`](crate::Oneshot), which has none of these, uses a lock-free
+/// slot.
+pub(crate) struct Locked<T> {
+    /// Messages in transit
+    pub(crate) data: VecDeque<T>,
+    /// Maximum number of messages in transit
+    pub(crate) capacity: usize,
+    /// Number of slots held by reservations and permits
+    pub(crate) reserved: usize,
+    /// True while a batch reservation holds slots, so other senders wait to
+    /// keep the batch contiguous
+    pub(crate) batch: bool,
+    /// Number of reservations waiting for free slots
+    pub(crate) reserving: usize,
+    /// True once the queue has been closed
+    pub(crate) closed: bool,
+    /// True once the queue has been sealed
+    pub(crate) sealed: bool,
+    /// Number of [`Channel`](crate::Channel) handles to the queue
+    pub(crate) handles: usize,
+    /// Number of [`Sender`](crate::Sender) handles to the queue
+    pub(crate) senders: usize,
+    /// Number of [`Receiver`](crate::Receiver) handles to the queue
+    pub(crate) receivers: usize,
+    /// True once no other handle can send to the receiving handles
+    pub(crate) disconnected: bool,
+    /// True once every handle that can receive has been dropped
+    pub(crate) abandoned: bool,
+    /// What sending does when full
+    pub(crate) policy: OverflowPolicy,
+    /// True once a panic while locked has closed the queue
+    pub(crate) poisoned: bool,
+    /// Number of messages pushed, wrapping, for noticing new messages
+    pub(crate) pushed: usize,
+}
+
+impl<T> Locked<T> {
+    /// Close the queue after a panic while locked
+    fn poison(&mut self) {
+        self.closed = true;
+        self.poisoned = true;
+    }
+
+    /// Return true if no more messages can be sent
+    pub(crate) fn send_closed(&self) -> bool {
+        self.closed || self.sealed || self.abandoned
+    }
+
+    /// Return true if no more messages can be received once drained
+    fn recv_closed(&self) -> bool {
+        self.sealed || self.disconnected
+    }
+
+    /// Return true if sending must wait for a receive, or for a batch
+    /// reservation to be used up
+    pub(crate) fn is_full(&self) -> bool {
+        self.batch || (self.policy.waits() && !self.has_room(1))
+    }
+
+    /// Return true if `n` slots are free, not counting reserved slots
+    pub(crate) fn has_room(&self, n: usize) -> bool {
+        self.data.len() + self.reserved + n <= self.capacity
+    }
+
+    /// Push a message following the overflow policy, returning the message
+    /// dropped to make room, if any
+    pub(crate) fn push(&mut self, message: T) -> Option<T> {
+        self.pushed = self.pushed.wrapping_add(1);
+
+        if self.has_room(1) {
+            self.data.push_back(message);
+            return None;
+        }
+
+        match self.policy {
+            // Only over capacity when a `Sink` sends without waiting for
+            // `poll_ready()`, or the capacity shrank since
+            OverflowPolicy::Block | OverflowPolicy::Fail => {
+                self.data.push_back(message);
+                None
+            }
+            OverflowPolicy::DropOldest => {
+                let displaced = self.data.pop_front();
+
+                self.data.push_back(message);
+                displaced
+            }
+            OverflowPolicy::DropNewest => Some(message),
+        }
+    }
+
+    /// Track new channel handles
+    pub(crate) fn add_handles(&mut self, role: Role, handles: usize) {
+        *self.count(role) += handles;
+        self.connect(false);
+    }
+
+    /// Stop tracking a dropped channel handle, returning whether receivers
+    /// and senders are disconnected
+    pub(crate) fn remove_handle(&mut self, role: Role) -> (bool, bool) {
+        *self.count(role) -= 1;
+        self.connect(true);
+        (self.disconnected, self.abandoned)
+    }
+
+    /// Get the number of handles with `role`
+    fn count(&mut self, role: Role) -> &mut usize {
+        match role {
+            Role::Both => &mut self.handles,
+            Role::Send => &mut self.senders,
+            Role::Recv => &mut self.receivers,
+        }
+    }
+
+    /// Update disconnection from the number of handles
+    ///
+    /// Receivers are disconnected once every handle that can send is
+    /// dropped, and senders once every handle that can receive is dropped.
+    /// A channel handle left `alone` by the others is disconnected too, since
+    /// it would only receive its own messages.
+    fn connect(&mut self, alone: bool) {
+        let senders = self.handles + self.senders;
+        let receivers = self.handles + self.receivers;
+
+        self.disconnected = (self.receivers > 0 && senders == 0)
+            || (alone
+                && self.handles == 1
+                && self.senders == 0
+                && self.receivers == 0);
+        self.abandoned = self.senders > 0 && receivers == 0;
+    }
+}
 
 /// A `Queue` can send messages to itself, and can be shared between threads
 /// and tasks.
 ///
-/// Implemented as a multi-producer/multi-consumer queue of size 1.
+/// Implemented as a multi-producer/multi-consumer queue of size 1 by default.
+/// Create a bounded queue that holds more messages with
+/// [`Queue::with_capacity()`], or resize with [`Queue::set_capacity()`].
 pub struct Queue<T = (), U: ?Sized = ()> {
     /// Data in transit
-    pub(crate) data: Mutex<T>,
+    pub(crate) data: Mutex<Locked<T>>,
+    /// Rolling throughput meter
+    #[cfg(feature = "metrics")]
+    pub(crate) meter: crate::metrics::Meter,
+    /// Backpressure callback
+    #[cfg(feature = "backpressure")]
+    pub(crate) backpressure: crate::backpressure::Hook,
     /// User data
     pub(crate) user: U,
 }
@@ -32,27 +197,110 @@ impl<T, U: ?Sized> core::ops::Deref for Queue<T, U> {
     }
 }
 
-impl<T, U: ?Sized + Default> Default for Queue<T, U> {
+impl<T, U: Default> Default for Queue<T, U> {
     fn default() -> Self {
         Self::with(U::default())
     }
 }
 
 impl<T> Queue<T> {
-    /// Create a new queue.
-    #[inline]
-    pub const fn new() -> Self {
-        Self::with(())
+    const_fn! {
+        /// Create a new queue.
+        #[inline]
+        pub const fn new() -> Self {
+            Self::with(())
+        }
+    }
+
+    const_fn! {
+        /// Create a new queue that can hold `capacity` messages in transit, so
+        /// senders can get ahead of receivers.
+        ///
+        /// # Panics
+        ///
+        /// If `capacity` is zero.
+        #[inline]
+        pub const fn with_capacity(capacity: usize) -> Self {
+            assert!(capacity != 0, "Capacity must be at least one");
+
+            Self::with_parts((), capacity, OverflowPolicy::Block)
+        }
+    }
+
+    const_fn! {
+        /// Create a new queue that can hold `capacity` messages in transit,
+        /// with `policy` deciding what sending does when full.
+        ///
+        /// # Panics
+        ///
+        /// If `capacity` is zero.
+        #[inline]
+        pub const fn with_policy(
+            capacity: usize,
+            policy: OverflowPolicy,
+        ) -> Self {
+            assert!(capacity != 0, "Capacity must be at least one");
+
+            Self::with_parts((), capacity, policy)
+        }
+    }
+
+    const_fn! {
+        /// Create a new queue that only holds the latest message.
+        ///
+        /// Sending never waits, and overwrites the message in transit if it
+        /// hasn't been received yet.
+        #[inline]
+        pub const fn latest() -> Self {
+            Self::with_policy(1, OverflowPolicy::DropOldest)
+        }
     }
 }
 
 impl<T, U> Queue<T, U> {
-    /// Create a new queue with associated data.
-    #[inline]
-    pub const fn with(user_data: U) -> Self {
-        Self {
-            data: Mutex::new(),
-            user: user_data,
+    const_fn! {
+        /// Create a new queue with associated data.
+        #[inline]
+        pub const fn with(user_data: U) -> Self {
+            Self::with_parts(user_data, 1, OverflowPolicy::Block)
+        }
+    }
+
+    const_fn! {
+        /// Create a new queue with associated data, capacity, and overflow
+        /// policy
+        const fn with_parts(
+            user_data: U,
+            capacity: usize,
+            policy: OverflowPolicy,
+        ) -> Self {
+            Self {
+                data: Mutex::with_poison(
+                    Locked {
+                        data: VecDeque::new(),
+                        capacity,
+                        reserved: 0,
+                        batch: false,
+                        reserving: 0,
+                        closed: false,
+                        sealed: false,
+                        handles: 0,
+                        senders: 0,
+                        receivers: 0,
+                        disconnected: false,
+                        abandoned: false,
+                        policy,
+                        poisoned: false,
+                        pushed: 0,
+                    },
+                    Locked::poison,
+                ),
+                #[cfg(feature = "metrics")]
+                meter: crate::metrics::Meter::new(),
+                #[cfg(feature = "backpressure")]
+                backpressure: crate::backpressure::Hook::new(),
+                user: user_data,
+            }
         }
     }
 }
@@ -60,45 +308,911 @@ impl<T, U> Queue<T, U> {
 impl<T, U: ?Sized> Queue<T, U> {
     /// Send a message on this queue.
     #[inline(always)]
-    pub async fn send(&self, message: T) {
-        Message(self, Cell::new(Some(message)), WakeHandle::new()).await
+    pub fn send(&self, message: T) -> Send<'_, T, U> {
+        Send(self, Some(message), WakeHandle::new(), false)
     }
 
     /// Receive a message from this queue.
     #[inline(always)]
-    pub async fn recv(&self) -> T {
+    pub fn recv(&self) -> Recv<'_, T, U> {
+        Recv(self, WakeHandle::new())
+    }
+
+    /// Close this queue, waking all waiting senders and receivers.
+    ///
+    /// Once closed, sending returns [`SendError`] and receiving returns
+    /// [`RecvError`].
+    pub fn close(&self) {
+        self.data.with(|locked| locked.closed = true);
+        self.data.wake_all();
+    }
+
+    /// Close this queue, and collect the messages still in transit.
+    ///
+    /// Closing and collecting happen under one lock, so every message is
+    /// either received, collected, or returned to its sender in a
+    /// [`SendError`].
+    pub fn close_and_collect(&self) -> Vec<T> {
+        let messages = self.data.with(|locked| {
+            locked.closed = true;
+            core::mem::take(&mut locked.data)
+        });
+
+        self.data.wake_all();
+        messages.into()
+    }
+
+    /// Return true if this queue has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.data.with(|locked| locked.closed)
+    }
+
+    /// Seal this queue, so that no more messages can be sent, waking all
+    /// waiting senders and receivers.
+    ///
+    /// Once sealed, sending returns [`SendError`], while receiving drains
+    /// the messages already in transit before returning [`RecvError`].
+    pub fn seal(&self) {
+        self.data.with(|locked| locked.sealed = true);
+        self.data.wake_all();
+    }
+
+    /// Return true if this queue has been sealed.
+    pub fn is_sealed(&self) -> bool {
+        self.data.with(|locked| locked.sealed)
+    }
+
+    /// Return true if a panic while this queue was locked closed it.
+    ///
+    /// This can only happen if a closure passed to the queue, or a waker,
+    /// panics.  Afterwards the queue behaves as if closed, so waiting tasks
+    /// return errors rather than waiting forever.
+    pub fn is_poisoned(&self) -> bool {
+        self.data.with(|locked| locked.poisoned)
+    }
+
+    /// Convert an error from receiving, telling poisoning apart
+    fn recv_error(&self, error: RecvError) -> RecvTimeoutError {
+        if self.is_poisoned() {
+            RecvTimeoutError::Poisoned
+        } else {
+            error.into()
+        }
+    }
+
+    /// Convert an error from sending, telling poisoning apart
+    fn send_error(&self, error: SendError<T>) -> SendTimeoutError<T> {
+        if self.is_poisoned() {
+            SendTimeoutError::Poisoned(error.0)
+        } else {
+            error.into()
+        }
+    }
+
+    /// Get the number of messages in transit.
+    pub fn len(&self) -> usize {
+        self.data.with(|locked| locked.data.len())
+    }
+
+    /// Return true if there are no messages in transit.
+    pub fn is_empty(&self) -> bool {
+        self.data.with(|locked| locked.data.is_empty())
+    }
+
+    /// Return true if sending would wait for room.
+    pub fn is_full(&self) -> bool {
+        self.data.with(|locked| locked.is_full())
+    }
+
+    /// Get the maximum number of messages that can be in transit at once.
+    pub fn capacity(&self) -> usize {
+        self.data.with(|locked| locked.capacity)
+    }
+
+    /// Change the maximum number of messages that can be in transit at once.
+    ///
+    /// When growing, waiting senders are woken.  When shrinking below the
+    /// number of messages currently in transit, no messages are discarded, but
+    /// sending waits until the queue has drained below the new capacity.
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero.
+    pub fn set_capacity(&self, capacity: usize) {
+        assert_ne!(capacity, 0, "Capacity must be at least one");
+
+        let grown = self.data.with(|locked| {
+            let grown = capacity > locked.capacity;
+
+            locked.capacity = capacity;
+            grown
+        });
+
+        if grown {
+            self.data.wake_all_send();
+        }
+    }
+
+    /// Wait until `n` slots are free, then reserve them for sending a batch of
+    /// messages that is received contiguously.
+    ///
+    /// Returns [`SendError`] if the queue has been closed or sealed.  Waits
+    /// until the capacity allows for `n` messages, so `n` should not exceed it.
+    pub async fn reserve(
+        &self,
+        n: usize,
+    ) -> Result<Reservation<'_, T, U>, SendError<()>> {
+        crate::reservation::reserve(self, n, true).await
+    }
+
+    /// Wait until a slot is free, then reserve it for sending one message.
+    ///
+    /// Returns [`SendError`] if the queue has been closed or sealed.  Unlike
+    /// a batch reservation, other senders can still use the remaining free
+    /// slots, and any number of permits can be held at once.
+    pub async fn reserve_one(&self) -> Result<Permit<'_, T, U>, SendError<()>> {
+        Ok(Permit(crate::reservation::reserve(self, 1, false).await?))
+    }
+
+    /// Install a callback invoked whenever a sender parks waiting for a free
+    /// slot, and when it stops waiting.
+    ///
+    /// Replaces any previously installed callback.  The callback is invoked
+    /// outside of the queue's lock.
+    #[cfg(feature = "backpressure")]
+    pub fn on_backpressure(
+        &self,
+        f: impl Fn(crate::BackpressureEvent) + core::marker::Send + Sync + 'static,
+    ) {
+        self.backpressure.set(Some(alloc::sync::Arc::new(f)));
+    }
+
+    /// Remove the callback installed with [`Queue::on_backpressure()`].
+    #[cfg(feature = "backpressure")]
+    pub fn clear_backpressure(&self) {
+        self.backpressure.set(None);
+    }
+
+    /// Try to send a message, registering the waker if not ready
+    ///
+    /// `parked` tracks whether the sender is waiting for a free slot.
+    pub(crate) fn poll_send(
+        &self,
+        cx: &mut Context<'_>,
+        wh: &mut WakeHandle,
+        message: &mut Option<T>,
+        parked: &mut bool,
+    ) -> Poll<Result<(), SendError<T>>> {
+        let was_parked = *parked;
+        let mut occupancy = 0;
+        let mut displaced = None;
+        let ret = self.data.poll_send(cx, wh, |locked| {
+            occupancy = locked.data.len();
+
+            if locked.send_closed() {
+                let message = message.take().expect("message already sent");
+
+                *parked = false;
+                return Poll::Ready(Err(SendError(message)));
+            }
+
+            // If can't send until receive, or until reservation is used up
+            if locked.is_full() {
+                *parked = true;
+                return Poll::Pending;
+            }
+
+            // Write to inner data
+            let message = message.take().expect("message already sent");
+
+            displaced = locked.push(message);
+            occupancy = locked.data.len();
+            *parked = false;
+
+            Poll::Ready(Ok(()))
+        });
+
+        // Drop an overwritten message outside of the lock
+        drop(displaced);
+
+        #[cfg(feature = "metrics")]
+        if let Poll::Ready(Ok(())) = ret {
+            self.meter.record();
+        }
+
+        #[cfg(feature = "backpressure")]
+        self.parked_changed(was_parked, *parked, occupancy);
+        #[cfg(not(feature = "backpressure"))]
+        let _ = (was_parked, occupancy);
+
+        ret
+    }
+
+    /// Fire a backpressure event if a sender parked or unparked
+    #[cfg(feature = "backpressure")]
+    pub(crate) fn parked_changed(
+        &self,
+        was_parked: bool,
+        parked: bool,
+        occupancy: usize,
+    ) {
+        use crate::BackpressureEvent::{Parked, Unparked};
+
+        match (was_parked, parked) {
+            (false, true) => self.backpressure.fire(Parked { occupancy }),
+            (true, false) => self.backpressure.fire(Unparked { occupancy }),
+            _ => {}
+        }
+    }
+
+    /// Try to receive a message, registering the waker if not ready
+    pub(crate) fn poll_recv(
+        &self,
+        cx: &mut Context<'_>,
+        wh: &mut WakeHandle,
+    ) -> Poll<Result<T, RecvError>> {
+        let mut reserving = false;
+        let ret = self.data.poll_recv(cx, wh, |locked| {
+            if locked.closed {
+                return Poll::Ready(Err(RecvError));
+            }
+
+            reserving = locked.reserving > 0;
+
+            // If can't receive until send
+            match locked.data.pop_front() {
+                Some(data) => Poll::Ready(Ok(data)),
+                None if locked.recv_closed() => Poll::Ready(Err(RecvError)),
+                None => Poll::Pending,
+            }
+        });
+
+        // A waiting reservation may need more than one free slot, so waking a
+        // single sender could leave a sender that can make progress asleep
+        if reserving && ret.is_ready() {
+            self.data.wake_all_send();
+        }
+
+        ret
+    }
+
+    /// Send every message from `messages`, pushing as many as fit under each
+    /// lock, and waking receivers once per batch.
+    ///
+    /// The iterator is advanced while the queue is locked, so it should be
+    /// cheap.  If the queue is closed, the unsent message is returned in a
+    /// [`SendError`], and the rest of the iterator is dropped.
+    pub async fn send_all(
+        &self,
+        messages: impl IntoIterator<Item = T>,
+    ) -> Result<(), SendError<T>> {
+        let mut messages = messages.into_iter();
+        let mut next = messages.next();
+        let mut wh = WakeHandle::new();
+
+        while next.is_some() {
+            let batch = future::poll_fn(|cx| {
+                self.data.poll_send(cx, &mut wh, |locked| {
+                    if locked.send_closed() {
+                        let message = next.take().expect("no message to send");
+
+                        return Poll::Ready(Err(SendError(message)));
+                    }
+
+                    let mut batch = 0;
+
+                    while !locked.is_full() {
+                        let Some(message) = next.take() else { break };
+
+                        // Drops any message displaced by the overflow policy
+                        locked.push(message);
+
+                        next = messages.next();
+                        batch += 1;
+                    }
+
+                    if batch == 0 {
+                        return Poll::Pending;
+                    }
+
+                    Poll::Ready(Ok(batch))
+                })
+            })
+            .await?;
+
+            // Only one receiver was woken, but more messages may be waiting
+            if batch > 1 {
+                self.data.wake_all_recv();
+            }
+
+            #[cfg(feature = "metrics")]
+            for _ in 0..batch {
+                self.meter.record();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Wait for at least one message, then move up to `limit` messages into
+    /// `buffer` under a single lock.
+    ///
+    /// Returns the number of messages received, which is zero only if
+    /// `limit` is zero or the queue is closed.
+    pub async fn recv_many(&self, buffer: &mut Vec<T>, limit: usize) -> usize {
+        if limit == 0 {
+            return 0;
+        }
+
+        let mut wh = WakeHandle::new();
+        let mut reserving = false;
+        let count = future::poll_fn(|cx| {
+            self.data.poll_recv(cx, &mut wh, |locked| {
+                if locked.closed {
+                    return Poll::Ready(0);
+                }
+
+                reserving = locked.reserving > 0;
+
+                let count = locked.data.len().min(limit);
+
+                if count == 0 {
+                    return if locked.recv_closed() {
+                        Poll::Ready(0)
+                    } else {
+                        Poll::Pending
+                    };
+                }
+
+                buffer.extend(locked.data.drain(..count));
+                Poll::Ready(count)
+            })
+        })
+        .await;
+
+        // Only one sender was woken, but more slots may be free
+        if count > 1 || (reserving && count > 0) {
+            self.data.wake_all_send();
+        }
+
+        count
+    }
+
+    /// Wait for at least one message, then receive up to `max` messages in
+    /// a chunk, under a single lock.
+    ///
+    /// Returns [`RecvError`] if closed, or sealed and drained.  The chunk is
+    /// only empty if `max` is zero.
+    pub async fn recv_chunk(&self, max: usize) -> Result<Vec<T>, RecvError> {
+        let mut chunk = Vec::new();
+
+        if max != 0 && self.recv_many(&mut chunk, max).await == 0 {
+            return Err(RecvError);
+        }
+
+        Ok(chunk)
+    }
+
+    /// Wait for room, then send a default message that `f` fills in place in
+    /// the channel's buffer, avoiding a move of a large message through the
+    /// stack.
+    ///
+    /// The queue is locked while `f` runs, so it should be quick.
+    pub async fn send_with(
+        &self,
+        f: impl FnOnce(&mut T),
+    ) -> Result<(), SendError<()>>
+    where
+        T: Default,
+    {
+        let mut f = Some(f);
+        let mut wh = WakeHandle::new();
+        let mut displaced = None;
+        let ret = future::poll_fn(|cx| {
+            self.data.poll_send(cx, &mut wh, |locked| {
+                if locked.send_closed() {
+                    return Poll::Ready(Err(SendError(())));
+                }
+
+                if locked.is_full() {
+                    return Poll::Pending;
+                }
+
+                displaced = locked.push(T::default());
+
+                // A dropped newest message never entered the buffer
+                if displaced.is_none()
+                    || locked.policy == OverflowPolicy::DropOldest
+                {
+                    if let Some(message) = locked.data.back_mut() {
+                        (f.take().expect("message already sent"))(message);
+                    }
+                }
+
+                Poll::Ready(Ok(()))
+            })
+        })
+        .await;
+
+        // Drop an overwritten message outside of the lock
+        drop(displaced);
+
+        #[cfg(feature = "metrics")]
+        if ret.is_ok() {
+            self.meter.record();
+        }
+
+        ret
+    }
+
+    /// Wait for a message, then remove it after `f` reads it in place in the
+    /// channel's buffer, avoiding a move of a large message through the
+    /// stack.
+    ///
+    /// The queue is locked while `f` runs, so it should be quick.
+    pub async fn recv_with<R>(
+        &self,
+        f: impl FnOnce(&mut T) -> R,
+    ) -> Result<R, RecvError> {
+        let mut f = Some(f);
+        let mut wh = WakeHandle::new();
+        let mut reserving = false;
+        let mut received = None;
+        let ret = future::poll_fn(|cx| {
+            self.data.poll_recv(cx, &mut wh, |locked| {
+                if locked.closed {
+                    return Poll::Ready(Err(RecvError));
+                }
+
+                reserving = locked.reserving > 0;
+
+                let Some(message) = locked.data.front_mut() else {
+                    return if locked.recv_closed() {
+                        Poll::Ready(Err(RecvError))
+                    } else {
+                        Poll::Pending
+                    };
+                };
+                let ret =
+                    (f.take().expect("message already received"))(message);
+
+                received = locked.data.pop_front();
+                Poll::Ready(Ok(ret))
+            })
+        })
+        .await;
+
+        // Drop the message outside of the lock
+        drop(received);
+
+        // A waiting reservation may need more than one free slot
+        if reserving && ret.is_ok() {
+            self.data.wake_all_send();
+        }
+
+        ret
+    }
+
+    /// Send a message if there is room, without waiting.
+    pub fn try_send(&self, message: T) -> Result<(), TrySendError<T>> {
+        self.replace(message).map(drop)
+    }
+
+    /// Send a message if there is room, without waiting, returning the
+    /// message dropped by the queue's [`OverflowPolicy`] if any.
+    pub fn replace(&self, message: T) -> Result<Option<T>, TrySendError<T>> {
+        let ret = self.data.with(|locked| {
+            if locked.poisoned {
+                return Err(TrySendError::Poisoned(message));
+            }
+
+            if locked.send_closed() {
+                return Err(TrySendError::Closed(message));
+            }
+
+            if locked.is_full() {
+                return Err(TrySendError::Full(message));
+            }
+
+            Ok(locked.push(message))
+        });
+
+        if ret.is_ok() {
+            self.data.wake_recv();
+
+            #[cfg(feature = "metrics")]
+            self.meter.record();
+        }
+
+        ret
+    }
+
+    /// Send a message, parking the current thread until there is room.
+    ///
+    /// This must not be called from async code, as it blocks the executor.
+    #[cfg(feature = "std")]
+    pub fn send_blocking(&self, message: T) -> Result<(), SendError<T>> {
+        crate::blocking::block_on(self.send(message))
+    }
+
+    /// Receive a message, parking the current thread until one is available.
+    ///
+    /// This must not be called from async code, as it blocks the executor.
+    #[cfg(feature = "std")]
+    pub fn recv_blocking(&self) -> Result<T, RecvError> {
+        crate::blocking::block_on(self.recv())
+    }
+
+    /// Receive a message if one is available, without waiting.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let (ret, reserving) = self.data.with(|locked| {
+            let ret = if locked.poisoned {
+                Err(TryRecvError::Poisoned)
+            } else if locked.closed {
+                Err(TryRecvError::Closed)
+            } else {
+                match locked.data.pop_front() {
+                    Some(message) => Ok(message),
+                    None if locked.recv_closed() => Err(TryRecvError::Closed),
+                    None => Err(TryRecvError::Empty),
+                }
+            };
+
+            (ret, locked.reserving > 0)
+        });
+
+        if ret.is_ok() {
+            if reserving {
+                self.data.wake_all_send();
+            } else {
+                self.data.wake_send();
+            }
+        }
+
+        ret
+    }
+
+    /// Receive a message from this queue, or time out once `deadline` is
+    /// reaped by `reaper`.
+    pub async fn recv_by<I: Ord + Clone>(
+        &self,
+        reaper: &Reaper<I>,
+        deadline: I,
+    ) -> Result<T, RecvTimeoutError> {
+        let timer = reaper.register(deadline);
         let mut wh = WakeHandle::new();
-        future::poll_fn(|cx| self.data.take(cx, &mut wh)).await
+
+        future::poll_fn(|cx| {
+            if let Poll::Ready(ret) = self.poll_recv(cx, &mut wh) {
+                return Poll::Ready(ret.map_err(|e| self.recv_error(e)));
+            }
+
+            timer
+                .poll_expired(cx)
+                .map(|()| Err(RecvTimeoutError::Timeout))
+        })
+        .await
+    }
+
+    /// Receive a message from this queue, or time out once a timer from
+    /// `sleep` for `timeout` completes.
+    pub async fn recv_timeout(
+        &self,
+        sleep: &impl Sleep,
+        timeout: Duration,
+    ) -> Result<T, RecvTimeoutError> {
+        self.recv_deadline(sleep.sleep(timeout)).await
+    }
+
+    /// Receive a message from this queue, or time out once `deadline`
+    /// completes.
+    pub async fn recv_deadline(
+        &self,
+        deadline: impl Future,
+    ) -> Result<T, RecvTimeoutError> {
+        let mut recv = self.recv();
+        let mut deadline = pin!(deadline);
+
+        future::poll_fn(|cx| {
+            if let Poll::Ready(ret) = Pin::new(&mut recv).poll(cx) {
+                return Poll::Ready(ret.map_err(|e| self.recv_error(e)));
+            }
+
+            deadline
+                .as_mut()
+                .poll(cx)
+                .map(|_| Err(RecvTimeoutError::Timeout))
+        })
+        .await
+    }
+
+    /// Receive a message, or `None` once `token` is cancelled.
+    pub async fn recv_until_cancelled(
+        &self,
+        token: &CancelToken,
+    ) -> Option<Result<T, RecvError>> {
+        match self.recv_deadline(token.cancelled()).await {
+            Ok(message) => Some(Ok(message)),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Closed | RecvTimeoutError::Poisoned) => {
+                Some(Err(RecvError))
+            }
+        }
+    }
+
+    /// Send a message on this queue, or time out once a timer from `sleep`
+    /// for `timeout` completes.
+    pub async fn send_timeout(
+        &self,
+        message: T,
+        sleep: &impl Sleep,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<T>> {
+        let mut send = self.send(message);
+        let mut timer = pin!(sleep.sleep(timeout));
+
+        future::poll_fn(|cx| {
+            if let Poll::Ready(ret) = Pin::new(&mut send).poll(cx) {
+                return Poll::Ready(ret.map_err(|e| self.send_error(e)));
+            }
+
+            timer.as_mut().poll(cx).map(|()| {
+                let message = send.1.take().expect("message already sent");
+
+                Err(SendTimeoutError::Timeout(message))
+            })
+        })
+        .await
+    }
+
+    /// Get the rolling estimate of messages sent per second over the last
+    /// second.
+    #[cfg(feature = "metrics")]
+    pub fn throughput(&self) -> f64 {
+        self.meter.throughput()
+    }
+
+    /// Receive messages from this queue, awaiting `handler` on each before
+    /// receiving the next, until closed.
+    pub async fn drain_to<F: Future<Output = ()>>(
+        &self,
+        mut handler: impl FnMut(T) -> F,
+    ) {
+        while let Ok(message) = self.recv().await {
+            handler(message).await;
+        }
+    }
+
+    /// Receive messages from this queue, passing each to `f`, until one
+    /// satisfies `is_last`.
+    ///
+    /// The last message is passed to `f` as well, and no messages are
+    /// received after it.  Returns [`RecvError`] if closed before the last
+    /// message is received.
+    pub async fn recv_until(
+        &self,
+        is_last: impl Fn(&T) -> bool,
+        mut f: impl FnMut(T),
+    ) -> Result<(), RecvError> {
+        loop {
+            let message = self.recv().await?;
+            let last = is_last(&message);
+
+            f(message);
+
+            if last {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Receive the first message that satisfies `f`, leaving the others for
+    /// other receivers.
+    ///
+    /// The queue is locked while `f` runs, so it should be quick.
+    pub async fn recv_if(
+        &self,
+        f: impl Fn(&T) -> bool,
+    ) -> Result<T, RecvError> {
+        let mut wh = WakeHandle::new();
+        let mut reserving = false;
+        let mut seen = None;
+        let ret = future::poll_fn(|cx| {
+            let mut rejected = false;
+            let poll = self.data.poll_recv(cx, &mut wh, |locked| {
+                if locked.closed {
+                    return Poll::Ready(Err(RecvError));
+                }
+
+                reserving = locked.reserving > 0;
+
+                if let Some(index) = locked.data.iter().position(&f) {
+                    let message = locked.data.remove(index);
+
+                    return Poll::Ready(message.ok_or(RecvError));
+                }
+
+                if locked.recv_closed() {
+                    return Poll::Ready(Err(RecvError));
+                }
+
+                rejected =
+                    !locked.data.is_empty() && seen != Some(locked.pushed);
+                seen = Some(locked.pushed);
+                Poll::Pending
+            });
+
+            // The wakeup may have been meant for a receiver that would accept
+            // the new messages, so pass it on to every receiver
+            if rejected {
+                self.data.wake_all_recv();
+            }
+
+            poll
+        })
+        .await;
+
+        // A waiting reservation may need more than one free slot
+        if reserving && ret.is_ok() {
+            self.data.wake_all_send();
+        }
+
+        ret
+    }
+
+    /// Inspect the next message to be received with `f`, without removing
+    /// it.
+    ///
+    /// Returns `None` if there is no message, or if closed.  The queue is
+    /// locked while `f` runs, so it should be quick.
+    pub fn peek_with<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        self.data.with(|locked| {
+            if locked.closed {
+                return None;
+            }
+
+            locked.data.front().map(f)
+        })
+    }
+}
+
+impl<T: Clone, U: ?Sized> Queue<T, U> {
+    /// Clone the next message to be received, without removing it.
+    ///
+    /// Returns `None` if there is no message, or if closed.  The message is
+    /// cloned while the queue is locked, so cloning a large message delays
+    /// other senders and receivers.
+    pub fn peek_clone(&self) -> Option<T> {
+        self.peek_with(T::clone)
+    }
+}
+
+impl<T: AsRef<[u8]>, U: ?Sized> Queue<T, U> {
+    /// Receive a message from this queue, and decode a borrow of its bytes
+    /// with `decode`.
+    ///
+    /// The message is dropped after decoding.
+    pub async fn recv_decode<R>(
+        &self,
+        decode: impl FnOnce(&[u8]) -> R,
+    ) -> Result<R, RecvError> {
+        let message = self.recv().await?;
+
+        Ok(decode(message.as_ref()))
     }
 }
 
 /// A message in the process of being sent over a [`Queue`].
-struct Message<'a, T, U: ?Sized>(&'a Queue<T, U>, Cell<Option<T>>, WakeHandle);
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Send<'a, T, U: ?Sized = ()>(
+    &'a Queue<T, U>,
+    Option<T>,
+    WakeHandle,
+    bool,
+);
 
-#[allow(unsafe_code)]
-impl<T, U: ?Sized> Message<'_, T, U> {
-    #[inline(always)]
-    fn pin_get_wh(self: Pin<&mut Self>) -> Pin<&mut WakeHandle> {
-        // This is okay because `1` is pinned when `self` is.
-        unsafe { self.map_unchecked_mut(|s| &mut s.2) }
+impl<T, U: ?Sized> core::fmt::Debug for Send<'_, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Send").finish_non_exhaustive()
     }
 }
 
-impl<T, U: ?Sized> Future for Message<'_, T, U> {
-    type Output = ();
+// `T` is never pinned, so `Send` is always `Unpin`
+impl<T, U: ?Sized> Unpin for Send<'_, T, U> {}
+
+impl<T, U: ?Sized> Future for Send<'_, T, U> {
+    type Output = Result<(), SendError<T>>;
 
     #[inline]
-    fn poll(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-    ) -> Poll<Self::Output> {
-        let mut wh = WakeHandle::new();
-        core::mem::swap(&mut wh, self.as_mut().pin_get_wh().get_mut());
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
 
-        let ret = self.0.data.store(&self.1, cx, &mut wh);
+        this.0.poll_send(cx, &mut this.2, &mut this.1, &mut this.3)
+    }
+}
 
-        core::mem::swap(&mut wh, self.as_mut().pin_get_wh().get_mut());
+impl<T, U: ?Sized> Drop for Send<'_, T, U> {
+    fn drop(&mut self) {
+        self.0.data.cancel_send(&mut self.2);
 
-        ret
+        // Canceled while waiting for a free slot
+        #[cfg(feature = "backpressure")]
+        if self.3 {
+            let occupancy = self.0.data.with(|locked| locked.data.len());
+
+            self.0.parked_changed(true, false, occupancy);
+        }
+    }
+}
+
+/// A message in the process of being received from a [`Queue`].
+///
+/// `Recv` is [`Unpin`], so it can be polled by reference from within a loop.
+///
+/// ```rust
+/// use whisk::Channel;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let numbers = Channel::new();
+///     let stop = Channel::new();
+///     let mut recv_stop = stop.recv();
+///
+///     tokio::spawn({
+///         let (numbers, stop) = (numbers.clone(), stop.clone());
+///
+///         async move {
+///             for i in 0..4 {
+///                 numbers.send(i).await.unwrap();
+///             }
+///             stop.send(()).await.unwrap();
+///         }
+///     });
+///
+///     let mut sum = 0;
+///     loop {
+///         tokio::select! {
+///             biased;
+///             Ok(number) = numbers.recv() => sum += number,
+///             Ok(()) = &mut recv_stop => break,
+///         }
+///     }
+///
+///     assert_eq!(sum, 6);
+/// }
+/// ```
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Recv<'a, T, U: ?Sized = ()>(&'a Queue<T, U>, WakeHandle);
+
+impl<T, U: ?Sized> core::fmt::Debug for Recv<'_, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Recv").finish_non_exhaustive()
+    }
+}
+
+impl<T, U: ?Sized> Future for Recv<'_, T, U> {
+    type Output = Result<T, RecvError>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        this.0.poll_recv(cx, &mut this.1)
+    }
+}
+
+impl<T, U: ?Sized> Drop for Recv<'_, T, U> {
+    fn drop(&mut self) {
+        self.0.data.cancel_recv(&mut self.1);
+    }
+}
+
+impl<'a, T, U: ?Sized> future::IntoFuture for &'a Queue<T, U> {
+    type IntoFuture = Recv<'a, T, U>;
+    type Output = Result<T, RecvError>;
+
+    #[inline(always)]
+    fn into_future(self) -> Recv<'a, T, U> {
+        self.recv()
     }
 }
@@ -0,0 +1,209 @@
+use alloc::{collections::BinaryHeap, vec::Vec};
+use core::{
+    cmp::{Ordering, Reverse},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{atomic::Arc, mutex::Mutex};
+
+/// State of one registered deadline
+struct TimerState {
+    /// True once the deadline has passed
+    expired: bool,
+    /// Waker of the receiver waiting on the deadline
+    waker: Option<Waker>,
+}
+
+/// A deadline in the reaper's heap, ordered by deadline only
+struct Entry<I> {
+    deadline: I,
+    timer: Arc<Mutex<TimerState>>,
+}
+
+impl<I: Ord> PartialEq for Entry<I> {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl<I: Ord> Eq for Entry<I> {}
+
+impl<I: Ord> PartialOrd for Entry<I> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<I: Ord> Ord for Entry<I> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.deadline.cmp(&other.deadline)
+    }
+}
+
+/// Deadlines protected by the mutex
+struct Locked<I> {
+    /// Earliest deadline first
+    heap: BinaryHeap<Reverse<Entry<I>>>,
+    /// Time of the last reap
+    now: Option<I>,
+}
+
+/// Shared expiry of receive deadlines, driven by a single background task
+///
+/// Receives register deadlines with
+/// [`Channel::recv_by()`](crate::Channel::recv_by), and the background task
+/// calls [`Reaper::reap()`] periodically, waking receivers whose deadlines have
+/// passed.  Deadlines are enforced with the resolution of that period.
+///
+/// The time type `I` is any ordered instant, such as
+/// [`std::time::Instant`](https://doc.rust-lang.org/std/time/struct.Instant.html)
+/// or a tick count.
+///
+/// ```rust
+/// use core::pin::pin;
+///
+/// use futures::poll;
+/// use whisk::{Channel, Reaper, RecvTimeoutError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let reaper = Reaper::new();
+///         let channel = Channel::new();
+///         let mut receiving = pin!(channel.recv_by(&reaper, 10));
+///
+///         assert!(poll!(&mut receiving).is_pending());
+///         assert_eq!(reaper.reap(5), Some(10));
+///         assert!(poll!(&mut receiving).is_pending());
+///         assert_eq!(reaper.reap(10), None);
+///         assert_eq!(receiving.await, Err(RecvTimeoutError::Timeout));
+///
+///         // Messages arriving before the deadline are received
+///         let sending = channel.send(7);
+///         let receiving = channel.recv_by(&reaper, 20);
+///         let (sent, received) =
+///             futures::future::join(sending, receiving).await;
+///
+///         sent.unwrap();
+///         assert_eq!(received, Ok(7));
+///     });
+/// }
+/// ```
+pub struct Reaper<I>(Arc<Mutex<Locked<I>>>);
+
+impl<I: Ord + Clone> Reaper<I> {
+    /// Create a new reaper.
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(Locked {
+            heap: BinaryHeap::new(),
+            now: None,
+        })))
+    }
+
+    /// Wake receivers with deadlines at or before `now`.
+    ///
+    /// Returns the next deadline, if any receivers are still waiting.
+    pub fn reap(&self, now: I) -> Option<I> {
+        let mut expired = Vec::new();
+        let next = self.0.with(|locked| {
+            while let Some(Reverse(entry)) = locked.heap.peek() {
+                if entry.deadline > now {
+                    break;
+                }
+
+                expired.extend(locked.heap.pop());
+            }
+
+            locked.now = Some(now);
+            locked
+                .heap
+                .peek()
+                .map(|Reverse(entry)| entry.deadline.clone())
+        });
+
+        // Wake outside of the lock
+        for Reverse(entry) in expired {
+            let waker = entry.timer.with(|timer| {
+                timer.expired = true;
+                timer.waker.take()
+            });
+
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+
+        next
+    }
+
+    /// Register a deadline
+    pub(crate) fn register(&self, deadline: I) -> Timer {
+        let timer = Arc::new(Mutex::new(TimerState {
+            expired: false,
+            waker: None,
+        }));
+
+        self.0.with(|locked| {
+            if locked.now.as_ref().is_some_and(|now| deadline <= *now) {
+                timer.with(|timer| timer.expired = true);
+            } else {
+                let timer = timer.clone();
+
+                locked.heap.push(Reverse(Entry { deadline, timer }));
+            }
+        });
+
+        Timer(timer)
+    }
+}
+
+impl<I> Clone for Reaper<I> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<I> core::fmt::Debug for Reaper<I> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Reaper").finish_non_exhaustive()
+    }
+}
+
+impl<I: Ord + Clone> Default for Reaper<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Registration of a deadline with a [`Reaper`]
+pub(crate) struct Timer(Arc<Mutex<TimerState>>);
+
+impl Timer {
+    /// Check if the deadline has passed, registering the waker if not
+    pub(crate) fn poll_expired(&self, cx: &mut Context<'_>) -> Poll<()> {
+        self.0.with(|timer| {
+            if timer.expired {
+                return Poll::Ready(());
+            }
+
+            if !timer
+                .waker
+                .as_ref()
+                .is_some_and(|w| w.will_wake(cx.waker()))
+            {
+                timer.waker = Some(cx.waker().clone());
+            }
+
+            Poll::Pending
+        })
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        // Don't keep the waker alive until the deadline is reaped
+        let waker = self.0.with(|timer| timer.waker.take());
+
+        drop(waker);
+    }
+}
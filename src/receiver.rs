@@ -0,0 +1,146 @@
+use alloc::vec::Vec;
+use core::task::{Context, Poll};
+
+use crate::{queue::Role, Channel, Recv, RecvError, TryRecvError};
+
+/// An owned handle that can only receive from a channel
+///
+/// Created with [`Channel::into_receiver()`] or [`Channel::split()`].  Enable the
+/// **`futures_core_3`** feature for `Receiver` to implement
+/// [`Stream`](futures_core_3::Stream) by value, yielding each message until
+/// the channel is closed or disconnected.
+///
+/// ```rust
+/// use whisk::{Channel, RecvError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let receiver = channel.clone().into_receiver();
+///
+///         channel.send(1).await.unwrap();
+///         assert_eq!(receiver.recv().await, Ok(1));
+///         channel.close();
+///         assert_eq!(receiver.recv().await, Err(RecvError));
+///     });
+/// }
+/// ```
+pub struct Receiver<T = (), U: ?Sized = ()>(Channel<T, U>);
+
+impl<T, U: ?Sized> Receiver<T, U> {
+    /// Receive a message from the channel.
+    #[inline(always)]
+    pub fn recv(&self) -> Recv<'_, T, U> {
+        self.0.recv()
+    }
+
+    /// Wait for at least one message, then receive up to `max` messages in
+    /// a chunk, under a single lock.
+    #[inline(always)]
+    pub async fn recv_chunk(&self, max: usize) -> Result<Vec<T>, RecvError> {
+        self.0.recv_chunk(max).await
+    }
+
+    /// Receive a message if one is available, without waiting.
+    #[inline(always)]
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        self.0.try_recv()
+    }
+
+    /// Poll for a message, registering the waker of `cx` if none is
+    /// available.
+    #[inline(always)]
+    pub fn poll_recv(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<T, RecvError>> {
+        self.0.poll_recv(cx)
+    }
+
+    /// Return true if the channel has been closed.
+    #[inline(always)]
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Convert back into the underlying channel handle.
+    #[inline(always)]
+    pub fn into_inner(self) -> Channel<T, U> {
+        self.0.into_role(Role::Both)
+    }
+}
+
+impl<T, U: ?Sized> Clone for Receiver<T, U> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T, U: ?Sized> core::fmt::Debug for Receiver<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Receiver").finish_non_exhaustive()
+    }
+}
+
+impl<T, U: ?Sized> From<Channel<T, U>> for Receiver<T, U> {
+    fn from(channel: Channel<T, U>) -> Self {
+        Self(channel.into_role(Role::Recv))
+    }
+}
+
+/// ```rust
+/// use futures::StreamExt;
+/// use whisk::Channel;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let receiver = channel.clone().into_receiver();
+///
+///         channel.set_capacity(3);
+///         for i in 0..3 {
+///             channel.send(i).await.unwrap();
+///         }
+///         channel.seal();
+///
+///         let doubled: Vec<_> = receiver.map(|i| i * 2).collect().await;
+///
+///         assert_eq!(doubled, [0, 2, 4]);
+///     });
+/// }
+/// ```
+#[cfg(feature = "futures_core_3")]
+impl<T, U: ?Sized> futures_core_3::Stream for Receiver<T, U> {
+    type Item = T;
+
+    fn poll_next(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<T>> {
+        let channel = &mut self.get_mut().0;
+
+        // Stay finished once the stream has ended
+        if channel.2 {
+            return Poll::Ready(None);
+        }
+
+        let Poll::Ready(item) = channel.poll_recv(cx) else {
+            return Poll::Pending;
+        };
+        let item = item.ok();
+
+        channel.2 = item.is_none();
+        Poll::Ready(item)
+    }
+}
+
+#[cfg(feature = "futures_core_3")]
+impl<T, U: ?Sized> futures_core_3::FusedStream for Receiver<T, U> {
+    fn is_terminated(&self) -> bool {
+        let Self(Channel(_, _, terminated, ..)) = self;
+
+        *terminated
+    }
+}
@@ -0,0 +1,253 @@
+use core::{future, mem, task::Poll};
+
+use crate::{atomic::Arc, wake_list::WakeHandle, Queue, SendError};
+
+/// Stops counting a reserver as waiting when dropped
+struct Reserving<'a, T, U: ?Sized> {
+    queue: &'a Queue<T, U>,
+    waiting: bool,
+}
+
+impl<T, U: ?Sized> Drop for Reserving<'_, T, U> {
+    fn drop(&mut self) {
+        if self.waiting {
+            let _occupancy = self.queue.data.with(|locked| {
+                locked.reserving -= 1;
+                locked.data.len()
+            });
+
+            #[cfg(feature = "backpressure")]
+            self.queue.parked_changed(true, false, _occupancy);
+        }
+    }
+}
+
+/// Wait for `n` free slots on `queue`, then reserve them
+///
+/// A `batch` reservation waits for every other reservation to be used up, and
+/// makes other senders wait until it is, so its messages are contiguous.
+pub(crate) async fn reserve<T, U: ?Sized>(
+    queue: &Queue<T, U>,
+    n: usize,
+    batch: bool,
+) -> Result<Reservation<'_, T, U>, SendError<()>> {
+    let mut reserving = Reserving {
+        queue,
+        waiting: false,
+    };
+    let mut wh = WakeHandle::new();
+
+    future::poll_fn(|cx| {
+        let was_waiting = reserving.waiting;
+        let mut occupancy = 0;
+        let ret = queue.data.poll_send(cx, &mut wh, |locked| {
+            occupancy = locked.data.len();
+
+            let ready = locked.send_closed()
+                || (!locked.batch
+                    && (!batch || locked.reserved == 0)
+                    && locked.has_room(n));
+
+            if ready && reserving.waiting {
+                locked.reserving -= 1;
+                reserving.waiting = false;
+            } else if !ready && !reserving.waiting {
+                locked.reserving += 1;
+                reserving.waiting = true;
+            }
+
+            if !ready {
+                return Poll::Pending;
+            }
+
+            if locked.send_closed() {
+                return Poll::Ready(Err(SendError(())));
+            }
+
+            locked.reserved += n;
+            locked.batch = batch;
+
+            Poll::Ready(Ok(Reservation {
+                queue,
+                remaining: n,
+                batch,
+            }))
+        });
+
+        #[cfg(feature = "backpressure")]
+        queue.parked_changed(was_waiting, reserving.waiting, occupancy);
+        #[cfg(not(feature = "backpressure"))]
+        let _ = (was_waiting, occupancy);
+
+        ret
+    })
+    .await
+}
+
+/// Free slots reserved on a [`Queue`] for sending a batch of messages
+///
+/// Other senders wait until the reservation is used up or dropped, so the
+/// batch is received contiguously.  Dropping the reservation releases any
+/// unused slots.
+#[must_use = "reserved slots are released when dropped"]
+pub struct Reservation<'a, T, U: ?Sized = ()> {
+    queue: &'a Queue<T, U>,
+    remaining: usize,
+    /// True if other senders wait until the reservation is used up
+    batch: bool,
+}
+
+impl<T, U: ?Sized> Reservation<'_, T, U> {
+    /// Send a message into one of the reserved slots.
+    ///
+    /// Returns [`SendError`] if the queue has been closed or sealed.
+    ///
+    /// # Panics
+    ///
+    /// If all of the reserved slots have already been used.
+    pub fn send(&mut self, message: T) -> Result<(), SendError<T>> {
+        assert_ne!(self.remaining, 0, "Reservation already used up");
+
+        let ret = self.queue.data.with(|locked| {
+            if locked.send_closed() {
+                return Err(SendError(message));
+            }
+
+            locked.data.push_back(message);
+            locked.pushed = locked.pushed.wrapping_add(1);
+            locked.reserved -= 1;
+
+            // Let other senders go once the batch is complete
+            if self.batch && self.remaining == 1 {
+                locked.batch = false;
+            }
+
+            Ok(())
+        });
+
+        if ret.is_ok() {
+            self.remaining -= 1;
+            self.queue.data.wake_recv();
+
+            #[cfg(feature = "metrics")]
+            self.queue.meter.record();
+
+            if self.remaining == 0 {
+                self.queue.data.wake_all_send();
+            }
+        }
+
+        ret
+    }
+
+    /// Get the number of reserved slots that haven't been used yet.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<T, U: ?Sized> Drop for Reservation<'_, T, U> {
+    fn drop(&mut self) {
+        if self.remaining == 0 {
+            return;
+        }
+
+        self.queue.data.with(|locked| {
+            locked.reserved -= self.remaining;
+
+            if self.batch {
+                locked.batch = false;
+            }
+        });
+        self.queue.data.wake_all_send();
+    }
+}
+
+impl<T, U: ?Sized> core::fmt::Debug for Reservation<'_, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Reservation")
+            .field("remaining", &self.remaining)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A free slot reserved on a [`Queue`] for sending one message
+///
+/// Waiting for a permit before building a message means an expensive message
+/// is only built once there's room for it.  Unlike a [`Reservation`], other
+/// senders don't wait for the permit, only for its slot, and any number of
+/// permits can be held at once.
+#[must_use = "the reserved slot is released when dropped"]
+pub struct Permit<'a, T, U: ?Sized = ()>(pub(crate) Reservation<'a, T, U>);
+
+impl<T, U: ?Sized> Permit<'_, T, U> {
+    /// Send a message into the reserved slot, without waiting.
+    ///
+    /// If the queue was closed or sealed since the slot was reserved, the
+    /// message is dropped.
+    pub fn send(mut self, message: T) {
+        let _ = self.0.send(message);
+    }
+}
+
+impl<T, U: ?Sized> core::fmt::Debug for Permit<'_, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Permit").finish_non_exhaustive()
+    }
+}
+
+/// A free slot reserved on a [`Queue`] for sending one message, which owns a
+/// reference to the queue
+///
+/// Unlike a [`Permit`], an owned permit can be moved into a spawned task.
+#[must_use = "the reserved slot is released when dropped"]
+pub struct OwnedPermit<T, U: ?Sized = ()> {
+    queue: Arc<Queue<T, U>>,
+    sent: bool,
+}
+
+impl<T, U: ?Sized> OwnedPermit<T, U> {
+    /// Wait until a slot is free on `queue`, then reserve it
+    pub(crate) async fn new(
+        queue: Arc<Queue<T, U>>,
+    ) -> Result<Self, SendError<()>> {
+        // Ownership of the reserved slot moves to the permit
+        mem::forget(reserve(&queue, 1, false).await?);
+
+        Ok(Self { queue, sent: false })
+    }
+
+    /// Send a message into the reserved slot, without waiting.
+    ///
+    /// If the queue was closed or sealed since the slot was reserved, the
+    /// message is dropped.
+    pub fn send(mut self, message: T) {
+        let mut reservation = Reservation {
+            queue: &self.queue,
+            remaining: 1,
+            batch: false,
+        };
+        let _ = reservation.send(message);
+
+        self.sent = true;
+    }
+}
+
+impl<T, U: ?Sized> Drop for OwnedPermit<T, U> {
+    fn drop(&mut self) {
+        // Release the slot if unused
+        drop(Reservation {
+            queue: &self.queue,
+            remaining: usize::from(!self.sent),
+            batch: false,
+        });
+    }
+}
+
+impl<T, U: ?Sized> core::fmt::Debug for OwnedPermit<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OwnedPermit")
+            .field("sent", &self.sent)
+            .finish_non_exhaustive()
+    }
+}
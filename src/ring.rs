@@ -0,0 +1,241 @@
+//! Fixed-capacity queues with inline message storage
+//!
+//! A [`Queue`] stores up to `N` messages in an inline ring buffer rather than
+//! on the heap, so it can live in a `static` on embedded targets, and sending
+//! and receiving never allocate for messages.
+//!
+//! ```rust
+//! use whisk::ring::Queue;
+//!
+//! /// Queue of up to 4 sensor readings, with no heap storage
+//! static READINGS: Queue<u32, 4> = Queue::new();
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         for reading in 0..4 {
+//!             READINGS.send(reading).await.unwrap();
+//!         }
+//!
+//!         assert!(READINGS.try_send(4).is_err());
+//!
+//!         for reading in 0..4 {
+//!             assert_eq!(READINGS.recv().await, Ok(reading));
+//!         }
+//!     });
+//! }
+//! ```
+
+use core::{future, mem::MaybeUninit, task::Poll};
+
+use crate::{
+    mutex::Mutex, wake_list::WakeHandle, RecvError, SendError, TryRecvError,
+    TrySendError,
+};
+
+/// Ring buffer protected by the mutex
+struct Ring<T, const N: usize> {
+    /// Message slots, initialized from `head` for `len` slots with wrapping
+    slots: [MaybeUninit<T>; N],
+    /// Index of the oldest message
+    head: usize,
+    /// Number of messages in the ring
+    len: usize,
+    /// True once the queue has been closed
+    closed: bool,
+}
+
+impl<T, const N: usize> Ring<T, N> {
+    /// Push a message, returning it back if the ring is full
+    fn push(&mut self, message: T) -> Result<(), T> {
+        if self.len == N {
+            return Err(message);
+        }
+
+        self.slots[(self.head + self.len) % N].write(message);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Pop the oldest message
+    fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // Safe because slots within `len` of `head` are initialized, and the
+        // slot is considered uninitialized once `head` moves past it
+        let message = unsafe { self.slots[self.head].assume_init_read() };
+
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(message)
+    }
+}
+
+impl<T, const N: usize> Drop for Ring<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+/// Unregisters a canceled send or receive, passing on its wakeup if woken
+struct Waiting<'a, T, const N: usize> {
+    queue: &'a Queue<T, N>,
+    wh: WakeHandle,
+    /// True for the send wake list, false for the receive wake list
+    send: bool,
+}
+
+impl<T, const N: usize> Drop for Waiting<'_, T, N> {
+    fn drop(&mut self) {
+        if self.send {
+            self.queue.0.cancel_send(&mut self.wh);
+        } else {
+            self.queue.0.cancel_recv(&mut self.wh);
+        }
+    }
+}
+
+/// An MPMC queue that holds up to `N` messages inline
+pub struct Queue<T, const N: usize>(Mutex<Ring<T, N>>);
+
+impl<T, const N: usize> Queue<T, N> {
+    const_fn! {
+        /// Create a new queue.
+        ///
+        /// # Panics
+        ///
+        /// If `N` is zero.
+        pub const fn new() -> Self {
+            assert!(N != 0, "Capacity must be at least one");
+
+            // Safe because an array of `MaybeUninit` doesn't need
+            // initialization
+            let slots = unsafe { MaybeUninit::uninit().assume_init() };
+
+            Self(Mutex::new(Ring {
+                slots,
+                head: 0,
+                len: 0,
+                closed: false,
+            }))
+        }
+    }
+
+    /// Send a message on this queue, waiting while it is full.
+    pub async fn send(&self, message: T) -> Result<(), SendError<T>> {
+        let mut message = Some(message);
+        let mut waiting = Waiting {
+            queue: self,
+            wh: WakeHandle::new(),
+            send: true,
+        };
+
+        future::poll_fn(|cx| {
+            self.0.poll_send(cx, &mut waiting.wh, |ring| {
+                let msg = message.take().expect("message already sent");
+
+                if ring.closed {
+                    return Poll::Ready(Err(SendError(msg)));
+                }
+
+                match ring.push(msg) {
+                    Ok(()) => Poll::Ready(Ok(())),
+                    Err(msg) => {
+                        message = Some(msg);
+                        Poll::Pending
+                    }
+                }
+            })
+        })
+        .await
+    }
+
+    /// Receive a message from this queue, waiting while it is empty.
+    pub async fn recv(&self) -> Result<T, RecvError> {
+        let mut waiting = Waiting {
+            queue: self,
+            wh: WakeHandle::new(),
+            send: false,
+        };
+
+        future::poll_fn(|cx| {
+            self.0.poll_recv(cx, &mut waiting.wh, |ring| {
+                if ring.closed {
+                    return Poll::Ready(Err(RecvError));
+                }
+
+                ring.pop().map_or(Poll::Pending, |msg| Poll::Ready(Ok(msg)))
+            })
+        })
+        .await
+    }
+
+    /// Send a message if there is room, without waiting.
+    pub fn try_send(&self, message: T) -> Result<(), TrySendError<T>> {
+        let ret = self.0.with(|ring| {
+            if ring.closed {
+                return Err(TrySendError::Closed(message));
+            }
+
+            ring.push(message).map_err(TrySendError::Full)
+        });
+
+        if ret.is_ok() {
+            self.0.wake_recv();
+        }
+
+        ret
+    }
+
+    /// Receive a message if one is available, without waiting.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let ret = self.0.with(|ring| {
+            if ring.closed {
+                return Err(TryRecvError::Closed);
+            }
+
+            ring.pop().ok_or(TryRecvError::Empty)
+        });
+
+        if ret.is_ok() {
+            self.0.wake_send();
+        }
+
+        ret
+    }
+
+    /// Get the number of messages in transit.
+    pub fn len(&self) -> usize {
+        self.0.with(|ring| ring.len)
+    }
+
+    /// Return true if there are no messages in transit.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the maximum number of messages that can be in transit at once.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Close this queue, waking all waiting senders and receivers.
+    pub fn close(&self) {
+        self.0.with(|ring| ring.closed = true);
+        self.0.wake_all();
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> core::fmt::Debug for Queue<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Queue").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,60 @@
+use core::future::Future;
+
+use crate::Channel;
+
+/// Closes and drains the channel when dropped
+struct Closing<T>(Channel<T>);
+
+impl<T> Drop for Closing<T> {
+    fn drop(&mut self) {
+        self.0.close();
+
+        // Drop any undelivered messages outside of the lock
+        let Channel(queue, ..) = &self.0;
+        let messages =
+            queue.data.with(|locked| core::mem::take(&mut locked.data));
+
+        drop(messages);
+    }
+}
+
+/// Run `f` with a new channel, closing it when the scope exits.
+///
+/// Any tasks still waiting on the channel are woken, receiving
+/// [`RecvError`](crate::RecvError) or [`SendError`](crate::SendError), and any
+/// undelivered messages are dropped.  This also happens if the returned future
+/// is dropped before completion.
+///
+/// ```rust
+/// use whisk::{channel_scope, Channel, RecvError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let handoff = Channel::new();
+///         let ack = Channel::new();
+///         let leaked_task = async {
+///             let channel: Channel<u32> = handoff.recv().await.unwrap();
+///
+///             ack.send(()).await.unwrap();
+///             channel.recv().await
+///         };
+///         let scope = channel_scope(|channel| async {
+///             handoff.send(channel).await.unwrap();
+///             ack.recv().await.unwrap();
+///         });
+///         let (received, ()) = futures::join!(leaked_task, scope);
+///
+///         assert_eq!(received, Err(RecvError));
+///     });
+/// }
+/// ```
+pub async fn channel_scope<T, F, R>(f: impl FnOnce(Channel<T>) -> F) -> R
+where
+    F: Future<Output = R>,
+{
+    let channel = Channel::new();
+    let _closing = Closing(channel.clone());
+
+    f(channel).await
+}
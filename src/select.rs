@@ -0,0 +1,243 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{AsyncReceiver, RecvError};
+
+/// A branch of a [`Select`]
+type Branch<'a, O> = Box<dyn FnMut(&mut Context<'_>) -> Poll<O> + 'a>;
+
+/// Future that receives from whichever of multiple channels is ready first
+///
+/// Channels may have different message types, and each is given a function
+/// converting its result into the output, such as an enum variant.  When more
+/// than one channel is ready, the one added first wins, and the others are
+/// left untouched.
+///
+/// ```rust
+/// use whisk::{Channel, RecvError, Select};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Event {
+///     Number(Result<u32, RecvError>),
+///     Text(Result<&'static str, RecvError>),
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let numbers = Channel::new();
+///         let text = Channel::new();
+///         let sending = text.send("hello");
+///         let selecting = Select::new()
+///             .recv(&numbers, Event::Number)
+///             .recv(&text, Event::Text);
+///         let (sent, event) = futures::future::join(sending, selecting).await;
+///
+///         sent.unwrap();
+///         assert_eq!(event, Event::Text(Ok("hello")));
+///     });
+/// }
+/// ```
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Select<'a, O>(Vec<Branch<'a, O>>);
+
+impl<'a, O> Select<'a, O> {
+    /// Create a new select with no channels.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Add a channel to receive from, converting its result with `f`.
+    pub fn recv<T: 'a, R: AsyncReceiver<T>>(
+        mut self,
+        receiver: &'a R,
+        f: impl FnOnce(Result<T, RecvError>) -> O + 'a,
+    ) -> Self {
+        let mut recv = Box::pin(receiver.recv());
+        let mut f = Some(f);
+
+        self.0.push(Box::new(move |cx| {
+            recv.as_mut().poll(cx).map(|result| {
+                let f = f.take().expect("select already completed");
+
+                f(result)
+            })
+        }));
+        self
+    }
+}
+
+impl<O> Default for Select<'_, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O> core::fmt::Debug for Select<'_, O> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Select").finish_non_exhaustive()
+    }
+}
+
+impl<O> Future for Select<'_, O> {
+    type Output = O;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<O> {
+        for branch in self.get_mut().0.iter_mut() {
+            if let Poll::Ready(output) = branch(cx) {
+                return Poll::Ready(output);
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Wait on multiple channel operations, running the arm of the first to
+/// complete.
+///
+/// Each arm is `pattern = future => body`, where the future is usually a
+/// `send()` or `recv()`.  Arms are separated by commas.  When more than one
+/// operation is ready, the first arm wins, and the other operations are
+/// canceled before its body runs.  Bodies run in the enclosing function, so
+/// they can use `break`, `continue`, `return`, and `?`.
+///
+/// If an output doesn't match its arm's pattern, that arm is disabled, and
+/// the others are still waited on.
+///
+/// # Panics
+///
+/// If every arm is disabled.
+///
+/// ```rust
+/// use whisk::Channel;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let requests = Channel::new();
+///         let replies = Channel::new();
+///         let worker = async {
+///             while let Ok(request) = requests.recv().await {
+///                 replies.send(request * 2).await.unwrap();
+///             }
+///         };
+///         let tasker = async {
+///             let mut next = 1;
+///             let mut total = 0;
+///
+///             while total < 12 {
+///                 whisk::select! {
+///                     sent = requests.send(next) => {
+///                         sent.unwrap();
+///                         next += 1;
+///                     },
+///                     reply = replies.recv() => total += reply.unwrap(),
+///                 }
+///             }
+///
+///             requests.close();
+///             assert_eq!(total, 12);
+///         };
+///
+///         futures::join!(worker, tasker);
+///     });
+/// }
+/// ```
+///
+/// Refutable patterns skip outputs they don't match, such as errors from
+/// closed channels.
+///
+/// ```rust
+/// use whisk::Channel;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let closed = Channel::<u32>::new();
+///         let open = Channel::new();
+///         let sending = async {
+///             open.send(2).await.unwrap();
+///         };
+///         let receiving = async {
+///             whisk::select! {
+///                 Ok(message) = closed.recv() => message,
+///                 Ok(message) = open.recv() => message * 2,
+///             }
+///         };
+///
+///         closed.close();
+///         assert_eq!(futures::join!(sending, receiving).1, 4);
+///     });
+/// }
+/// ```
+#[macro_export]
+macro_rules! select {
+    ($($pat:pat = $fut:expr => $body:expr),+ $(,)?) => {{
+        $crate::select! { @outs [] $($pat = $fut => $body,)+ }
+    }};
+    (@outs [$($done:tt)*] $pat:pat = $fut:expr => $body:expr, $($rest:tt)*) => {
+        // `Some(None)` once disabled by an output that doesn't match
+        let mut out = ::core::option::Option::None;
+        $crate::select! { @outs [$($done)* [out ($pat) ($fut) ($body)]] $($rest)* }
+    };
+    (@outs [$([$out:ident ($pat:pat) ($fut:expr) ($body:expr)])*]) => {
+        {
+            $crate::select! { @futs [] $([$out ($pat) ($fut)])* }
+        }
+        $(
+            if let ::core::option::Option::Some(
+                ::core::option::Option::Some($pat),
+            ) = $out {
+                $body
+            } else
+        )* {
+            ::core::panic!("every `select!` arm was disabled")
+        }
+    };
+    (@futs [$($done:tt)*] [$out:ident ($pat:pat) ($fut:expr)] $($rest:tt)*) => {
+        let mut fut = ::core::pin::pin!($fut);
+        $crate::select! { @futs [$($done)* [$out ($pat) fut]] $($rest)* }
+    };
+    (@futs [$([$out:ident ($pat:pat) $f:ident])*]) => {
+        ::core::future::poll_fn(|cx| {
+            $(
+                if $out.is_none() {
+                    let poll = ::core::future::Future::poll($f.as_mut(), cx);
+
+                    if let ::core::task::Poll::Ready(output) = poll {
+                        #[allow(unused_variables)]
+                        let matched = match &output {
+                            $pat => true,
+                            #[allow(unreachable_patterns)]
+                            _ => false,
+                        };
+
+                        if matched {
+                            $out = ::core::option::Option::Some(
+                                ::core::option::Option::Some(output),
+                            );
+                            return ::core::task::Poll::Ready(());
+                        }
+
+                        $out = ::core::option::Option::Some(
+                            ::core::option::Option::None,
+                        );
+                    }
+                }
+            )*
+
+            // Every arm is disabled
+            if true $(&& $out.is_some())* {
+                return ::core::task::Poll::Ready(());
+            }
+
+            ::core::task::Poll::Pending
+        })
+        .await
+    };
+}
@@ -0,0 +1,134 @@
+use core::{future, task::Poll};
+
+use crate::{mutex::Mutex, wake_list::WakeHandle};
+
+/// Unregisters a canceled acquire, passing on its wakeup if woken
+struct Acquiring<'a>(&'a Semaphore, WakeHandle);
+
+impl Drop for Acquiring<'_> {
+    fn drop(&mut self) {
+        self.0.permits.cancel_recv(&mut self.1);
+    }
+}
+
+/// An async semaphore, limiting how many tasks can hold a permit at once
+///
+/// Waiting tasks are woken with the same wake lists channels use, so it
+/// works without the standard library.
+///
+/// ```rust
+/// use whisk::Semaphore;
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let semaphore = Semaphore::new(2);
+///         let a = semaphore.acquire().await;
+///         let b = semaphore.acquire().await;
+///
+///         assert_eq!(semaphore.available_permits(), 0);
+///         assert!(semaphore.try_acquire().is_none());
+///
+///         // Waits until a permit is released
+///         let releasing = async { drop(a) };
+///         let (c, ()) = futures::join!(semaphore.acquire(), releasing);
+///
+///         drop((b, c));
+///         semaphore.add_permits(1);
+///         assert_eq!(semaphore.available_permits(), 3);
+///     });
+/// }
+/// ```
+pub struct Semaphore {
+    permits: Mutex<usize>,
+}
+
+impl Semaphore {
+    const_fn! {
+        /// Create a new semaphore with `permits` permits available.
+        pub const fn new(permits: usize) -> Self {
+            Self {
+                permits: Mutex::new(permits),
+            }
+        }
+    }
+
+    /// Get the number of permits available.
+    pub fn available_permits(&self) -> usize {
+        self.permits.with(|permits| *permits)
+    }
+
+    /// Make `n` more permits available, waking waiting tasks.
+    pub fn add_permits(&self, n: usize) {
+        self.permits.with(|permits| *permits += n);
+        self.permits.wake_recv();
+    }
+
+    /// Wait for a permit to be available, then acquire it.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let mut acquiring = Acquiring(self, WakeHandle::new());
+        let mut remaining = 0;
+
+        future::poll_fn(|cx| {
+            self.permits.poll_recv(cx, &mut acquiring.1, |permits| {
+                if *permits == 0 {
+                    return Poll::Pending;
+                }
+
+                *permits -= 1;
+                remaining = *permits;
+                Poll::Ready(())
+            })
+        })
+        .await;
+
+        // Only one task was woken, but more permits may be available
+        if remaining > 0 {
+            self.permits.wake_recv();
+        }
+
+        SemaphorePermit(self)
+    }
+
+    /// Acquire a permit if one is available, without waiting.
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        self.permits.with(|permits| {
+            let permit = permits.checked_sub(1)?;
+
+            *permits = permit;
+            Some(SemaphorePermit(self))
+        })
+    }
+}
+
+impl core::fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Semaphore")
+            .field("available_permits", &self.available_permits())
+            .finish()
+    }
+}
+
+/// A permit acquired from a [`Semaphore`], released when dropped
+#[must_use = "the permit is released immediately if not held"]
+pub struct SemaphorePermit<'a>(&'a Semaphore);
+
+impl SemaphorePermit<'_> {
+    /// Consume the permit without releasing it, reducing the semaphore's
+    /// permits by one.
+    pub fn forget(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.0.add_permits(1);
+    }
+}
+
+impl core::fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SemaphorePermit").finish_non_exhaustive()
+    }
+}
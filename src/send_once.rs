@@ -0,0 +1,71 @@
+use crate::{Channel, SendError};
+
+/// A token that can send exactly one message on a channel
+///
+/// Created with [`Channel::into_send_once()`], sending consumes the token so
+/// that replying twice is a compile error.
+///
+/// ```rust
+/// use whisk::{Channel, SendOnce};
+///
+/// enum Cmd {
+///     /// Tell messenger to add
+///     Add(u32, u32, SendOnce<u32>),
+/// }
+///
+/// async fn worker_main(commands: Channel<Cmd>) {
+///     while let Ok(command) = commands.recv().await {
+///         match command {
+///             Cmd::Add(a, b, s) => s.send(a + b).await.unwrap(),
+///         }
+///     }
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let worker = worker_main(channel.clone());
+///         let tasker = async {
+///             let oneshot = Channel::new();
+///             let reply = oneshot.clone().into_send_once();
+///
+///             channel.send(Cmd::Add(43, 400, reply)).await.unwrap();
+///             assert_eq!(oneshot.recv().await, Ok(443));
+///             channel.close();
+///         };
+///
+///         futures::future::join(worker, tasker).await;
+///     });
+/// }
+/// ```
+///
+/// ```rust,compile_fail
+/// let reply = whisk::Channel::new().into_send_once();
+///
+/// async move {
+///     let _ = reply.send(1).await;
+///     let _ = reply.send(2).await;
+/// };
+/// ```
+pub struct SendOnce<T = (), U: ?Sized = ()>(Channel<T, U>);
+
+impl<T, U: ?Sized> SendOnce<T, U> {
+    /// Send a message, consuming this token.
+    #[inline(always)]
+    pub async fn send(self, message: T) -> Result<(), SendError<T>> {
+        self.0.send(message).await
+    }
+}
+
+impl<T, U: ?Sized> core::fmt::Debug for SendOnce<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SendOnce").finish_non_exhaustive()
+    }
+}
+
+impl<T, U: ?Sized> From<Channel<T, U>> for SendOnce<T, U> {
+    fn from(channel: Channel<T, U>) -> Self {
+        Self(channel)
+    }
+}
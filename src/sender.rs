@@ -0,0 +1,115 @@
+use core::task::{Context, Poll};
+
+use crate::{queue::Role, Channel, Send, SendError, TrySendError};
+
+/// An owned handle that can only send on a channel
+///
+/// Created with [`Channel::into_sender()`] or [`Channel::split()`].  When
+/// every sender is dropped, leaving only receivers, receivers see the channel
+/// as disconnected once drained.  When every receiver is dropped, sending
+/// returns an error with the message.
+///
+/// ```rust
+/// use whisk::{Channel, RecvError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let (sender, receiver) = Channel::new().split();
+///
+///         sender.send(1).await.unwrap();
+///         drop(sender);
+///         assert_eq!(receiver.recv().await, Ok(1));
+///         assert_eq!(receiver.recv().await, Err(RecvError));
+///     });
+/// }
+/// ```
+///
+/// Every receiver sees the disconnection, not just the last one.
+///
+/// ```rust
+/// use whisk::{Channel, RecvError};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let (sender, receiver) = Channel::with_capacity(2).split();
+///         let receivers = [receiver.clone(), receiver.clone(), receiver];
+///         let waiting = async {
+///             futures::future::join_all(receivers.iter().map(|r| r.recv()))
+///                 .await
+///         };
+///         let sending = async move {
+///             sender.send(1).await.unwrap();
+///             sender.send(2).await.unwrap();
+///         };
+///         let (received, ()) = futures::join!(waiting, sending);
+///         let mut messages: Vec<_> =
+///             received.iter().filter_map(|r| r.ok()).collect();
+///
+///         messages.sort_unstable();
+///         assert_eq!(messages, [1, 2]);
+///         assert!(received.contains(&Err(RecvError)));
+///
+///         for receiver in &receivers {
+///             assert_eq!(receiver.recv().await, Err(RecvError));
+///         }
+///     });
+/// }
+/// ```
+pub struct Sender<T = (), U: ?Sized = ()>(Channel<T, U>);
+
+impl<T, U: ?Sized> Sender<T, U> {
+    /// Send a message on the channel.
+    #[inline(always)]
+    pub fn send(&self, message: T) -> Send<'_, T, U> {
+        self.0.send(message)
+    }
+
+    /// Send a message if there is room, without waiting.
+    #[inline(always)]
+    pub fn try_send(&self, message: T) -> Result<(), TrySendError<T>> {
+        self.0.try_send(message)
+    }
+
+    /// Poll to send the message in `message`, registering the waker of `cx`
+    /// if there is no room.
+    #[inline(always)]
+    pub fn poll_send(
+        &mut self,
+        cx: &mut Context<'_>,
+        message: &mut Option<T>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        self.0.poll_send(cx, message)
+    }
+
+    /// Return true if the channel has been closed.
+    #[inline(always)]
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Convert back into the underlying channel handle.
+    #[inline(always)]
+    pub fn into_inner(self) -> Channel<T, U> {
+        self.0.into_role(Role::Both)
+    }
+}
+
+impl<T, U: ?Sized> Clone for Sender<T, U> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T, U: ?Sized> core::fmt::Debug for Sender<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+impl<T, U: ?Sized> From<Channel<T, U>> for Sender<T, U> {
+    fn from(channel: Channel<T, U>) -> Self {
+        Self(channel.into_role(Role::Send))
+    }
+}
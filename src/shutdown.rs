@@ -0,0 +1,117 @@
+use core::{future, task::Poll};
+
+use crate::{
+    atomic::{Arc, AtomicBool, Ordering::SeqCst},
+    wake_list::{WakeHandle, WakeList},
+};
+
+struct Shared {
+    /// True once triggered
+    triggered: AtomicBool,
+    /// List of waiting tasks
+    waiters: WakeList,
+}
+
+/// A signal that is triggered once, waking every task waiting on it
+///
+/// Unlike receiving from a [`Channel`](crate::Channel), waiting doesn't
+/// consume the signal, so any number of tasks can wait for it, and it stays
+/// triggered.
+///
+/// ```rust
+/// use whisk::Shutdown;
+///
+/// async fn worker(shutdown: &Shutdown) -> &'static str {
+///     shutdown.wait().await;
+///     "stopped"
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let shutdown = Shutdown::new();
+///         let trigger = async {
+///             assert!(!shutdown.is_triggered());
+///             shutdown.trigger();
+///         };
+///         let (a, b, ()) =
+///             futures::join!(worker(&shutdown), worker(&shutdown), trigger);
+///
+///         assert_eq!((a, b), ("stopped", "stopped"));
+///         assert!(shutdown.is_triggered());
+///
+///         // Already triggered, so doesn't wait
+///         shutdown.wait().await;
+///     });
+/// }
+/// ```
+pub struct Shutdown(Arc<Shared>);
+
+impl Shutdown {
+    /// Create a new shutdown signal that hasn't been triggered.
+    pub fn new() -> Self {
+        Self(Arc::new(Shared {
+            triggered: AtomicBool::new(false),
+            waiters: WakeList::new(),
+        }))
+    }
+
+    /// Trigger the signal, waking every waiting task.
+    ///
+    /// Returns false if it was already triggered.
+    pub fn trigger(&self) -> bool {
+        let triggered = !self.0.triggered.swap(true, SeqCst);
+
+        if triggered {
+            self.0.waiters.wake_all();
+        }
+
+        triggered
+    }
+
+    /// Return true if the signal has been triggered.
+    pub fn is_triggered(&self) -> bool {
+        self.0.triggered.load(SeqCst)
+    }
+
+    /// Wait until the signal is triggered.
+    pub async fn wait(&self) {
+        let mut wh = WakeHandle::new();
+
+        future::poll_fn(|cx| {
+            if self.is_triggered() {
+                return Poll::Ready(());
+            }
+
+            wh.register(&self.0.waiters, cx.waker());
+
+            // Check again in case of triggering before registering
+            if self.is_triggered() {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl Clone for Shutdown {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Shutdown")
+            .field("triggered", &self.is_triggered())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::{Condvar, Mutex, PoisonError};
+
+/// Blocking lock built on the standard library's mutex and condition variable
+///
+/// Waiting threads sleep until the lock is released, rather than spinning on
+/// a holder that the OS has descheduled.
+pub(crate) struct StdLock {
+    /// True while the lock is held
+    locked: Mutex<bool>,
+    /// Notified when the lock is released
+    released: Condvar,
+}
+
+impl StdLock {
+    /// Create a new unlocked lock
+    pub(crate) const fn new() -> Self {
+        Self {
+            locked: Mutex::new(false),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Try to acquire the lock, returning true if acquired
+    pub(crate) fn try_lock(&self) -> bool {
+        let mut locked =
+            self.locked.lock().unwrap_or_else(PoisonError::into_inner);
+
+        !core::mem::replace(&mut *locked, true)
+    }
+
+    /// Block until the lock is acquired
+    pub(crate) fn lock(&self) {
+        let locked = self.locked.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut locked = self
+            .released
+            .wait_while(locked, |locked| *locked)
+            .unwrap_or_else(PoisonError::into_inner);
+
+        *locked = true;
+    }
+
+    /// Release the lock
+    pub(crate) fn unlock(&self) {
+        *self.locked.lock().unwrap_or_else(PoisonError::into_inner) = false;
+        self.released.notify_one();
+    }
+}
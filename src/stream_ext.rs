@@ -0,0 +1,326 @@
+//! Stream combinators that don't depend on `futures-util`
+//!
+//! [`StreamExt`] adds a few adapters to every [`Stream`], such as whisk's
+//! [`Receiver`], so streams can be processed without a heavier dependency,
+//! including in `no_std`.  Adapters require the stream to be [`Unpin`],
+//! which whisk's streams are.
+//!
+//! [`Receiver`]: crate::Receiver
+//!
+//! ```rust
+//! use std::cell::Cell;
+//!
+//! use whisk::{stream_ext::StreamExt, CancelToken, Channel};
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let channel = Channel::with_capacity(4);
+//!         let token = CancelToken::new();
+//!         let total = Cell::new(0);
+//!
+//!         for i in 1..=4 {
+//!             channel.send(i).await.unwrap();
+//!         }
+//!         channel.seal();
+//!         channel
+//!             .clone()
+//!             .into_receiver()
+//!             .map(|i| i * 10)
+//!             .filter_map(|i| (i != 30).then_some(i))
+//!             .take_until(&token)
+//!             .for_each_concurrent(2, |i| {
+//!                 let total = &total;
+//!
+//!                 async move { total.set(total.get() + i) }
+//!             })
+//!             .await;
+//!
+//!         assert_eq!(total.get(), 70);
+//!     });
+//! }
+//! ```
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_core_3::{FusedStream, Stream};
+
+use crate::{wake_list::WakeHandle, CancelToken};
+
+/// Stream adapters, implemented for every [`Stream`]
+pub trait StreamExt: Stream {
+    /// Convert each item with `f`.
+    fn map<U, F>(self, f: F) -> Map<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> U,
+    {
+        Map { stream: self, f }
+    }
+
+    /// Convert each item with `f`, skipping items it returns `None` for.
+    fn filter_map<U, F>(self, f: F) -> FilterMap<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> Option<U>,
+    {
+        FilterMap { stream: self, f }
+    }
+
+    /// End the stream once `token` is cancelled.
+    fn take_until(self, token: &CancelToken) -> TakeUntil<Self>
+    where
+        Self: Sized,
+    {
+        TakeUntil {
+            stream: self,
+            wh: WakeHandle::new(),
+            token: token.clone(),
+            done: false,
+        }
+    }
+
+    /// Run `f` on each item, with up to `limit` of the returned futures
+    /// running at once, completing once the stream ends and every future
+    /// has completed.
+    ///
+    /// A `limit` of zero means no limit.
+    fn for_each_concurrent<Fut, F>(
+        self,
+        limit: usize,
+        f: F,
+    ) -> ForEachConcurrent<Self, F, Fut>
+    where
+        Self: Sized,
+        F: FnMut(Self::Item) -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        ForEachConcurrent {
+            stream: self,
+            f,
+            running: Vec::new(),
+            limit,
+            done: false,
+        }
+    }
+}
+
+impl<S: Stream + ?Sized> StreamExt for S {}
+
+/// Stream returned from [`StreamExt::map()`]
+#[must_use = "streams do nothing unless polled"]
+pub struct Map<S, F> {
+    stream: S,
+    f: F,
+}
+
+// The function is never pinned
+impl<S: Unpin, F> Unpin for Map<S, F> {}
+
+impl<S, F> core::fmt::Debug for Map<S, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Map").finish_non_exhaustive()
+    }
+}
+
+impl<S, F, U> Stream for Map<S, F>
+where
+    S: Stream + Unpin,
+    F: FnMut(S::Item) -> U,
+{
+    type Item = U;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<U>> {
+        let this = self.get_mut();
+
+        Pin::new(&mut this.stream)
+            .poll_next(cx)
+            .map(|item| item.map(&mut this.f))
+    }
+}
+
+impl<S, F, U> FusedStream for Map<S, F>
+where
+    S: FusedStream + Unpin,
+    F: FnMut(S::Item) -> U,
+{
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+/// Stream returned from [`StreamExt::filter_map()`]
+#[must_use = "streams do nothing unless polled"]
+pub struct FilterMap<S, F> {
+    stream: S,
+    f: F,
+}
+
+// The function is never pinned
+impl<S: Unpin, F> Unpin for FilterMap<S, F> {}
+
+impl<S, F> core::fmt::Debug for FilterMap<S, F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FilterMap").finish_non_exhaustive()
+    }
+}
+
+impl<S, F, U> Stream for FilterMap<S, F>
+where
+    S: Stream + Unpin,
+    F: FnMut(S::Item) -> Option<U>,
+{
+    type Item = U;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<U>> {
+        let this = self.get_mut();
+
+        loop {
+            let poll = Pin::new(&mut this.stream).poll_next(cx);
+            let Poll::Ready(item) = poll else {
+                return Poll::Pending;
+            };
+            let Some(item) = item else {
+                return Poll::Ready(None);
+            };
+
+            if let Some(item) = (this.f)(item) {
+                return Poll::Ready(Some(item));
+            }
+        }
+    }
+}
+
+impl<S, F, U> FusedStream for FilterMap<S, F>
+where
+    S: FusedStream + Unpin,
+    F: FnMut(S::Item) -> Option<U>,
+{
+    fn is_terminated(&self) -> bool {
+        self.stream.is_terminated()
+    }
+}
+
+/// Stream returned from [`StreamExt::take_until()`]
+#[must_use = "streams do nothing unless polled"]
+pub struct TakeUntil<S> {
+    stream: S,
+    /// Dropped before the token, which owns the list it's registered on
+    wh: WakeHandle,
+    token: CancelToken,
+    /// True once the stream has ended
+    done: bool,
+}
+
+impl<S> core::fmt::Debug for TakeUntil<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("TakeUntil")
+            .field("token", &self.token)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: Stream + Unpin> Stream for TakeUntil<S> {
+    type Item = S::Item;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<S::Item>> {
+        let this = self.get_mut();
+
+        if this.done || this.token.poll_cancelled(cx, &mut this.wh).is_ready() {
+            this.done = true;
+            return Poll::Ready(None);
+        }
+
+        let Poll::Ready(item) = Pin::new(&mut this.stream).poll_next(cx) else {
+            return Poll::Pending;
+        };
+
+        this.done = item.is_none();
+        Poll::Ready(item)
+    }
+}
+
+impl<S: Stream + Unpin> FusedStream for TakeUntil<S> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+/// Future returned from [`StreamExt::for_each_concurrent()`]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ForEachConcurrent<S, F, Fut> {
+    stream: S,
+    f: F,
+    /// Futures returned from `f` that haven't completed
+    running: Vec<Pin<Box<Fut>>>,
+    limit: usize,
+    /// True once the stream has ended
+    done: bool,
+}
+
+// The function is never pinned, and the futures are pinned on the heap
+impl<S: Unpin, F, Fut> Unpin for ForEachConcurrent<S, F, Fut> {}
+
+impl<S, F, Fut> core::fmt::Debug for ForEachConcurrent<S, F, Fut> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ForEachConcurrent")
+            .field("running", &self.running.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, F, Fut> Future for ForEachConcurrent<S, F, Fut>
+where
+    S: Stream + Unpin,
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+
+        loop {
+            // Start futures for new items while under the limit
+            while !this.done
+                && (this.limit == 0 || this.running.len() < this.limit)
+            {
+                match Pin::new(&mut this.stream).poll_next(cx) {
+                    Poll::Ready(Some(item)) => {
+                        this.running.push(Box::pin((this.f)(item)));
+                    }
+                    Poll::Ready(None) => this.done = true,
+                    Poll::Pending => break,
+                }
+            }
+
+            let running = this.running.len();
+
+            this.running
+                .retain_mut(|fut| fut.as_mut().poll(cx).is_pending());
+
+            if this.done && this.running.is_empty() {
+                return Poll::Ready(());
+            }
+
+            // Only poll the stream again if room was made for more items
+            if this.done || this.running.len() == running {
+                return Poll::Pending;
+            }
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Async locks
+//!
+//! Tasks waiting for a lock are parked on the same wake lists channels use,
+//! so these work without the standard library.
+//!
+//! ```rust
+//! use whisk::sync::{Mutex, RwLock};
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let counter = Mutex::new(0);
+//!         let increment = || async {
+//!             let mut count = counter.lock().await;
+//!
+//!             *count += 1;
+//!         };
+//!
+//!         futures::join!(increment(), increment());
+//!         assert_eq!(*counter.lock().await, 2);
+//!
+//!         let config = RwLock::new("fast");
+//!         let (a, b) = futures::join!(config.read(), config.read());
+//!
+//!         assert_eq!((*a, *b), ("fast", "fast"));
+//!         drop((a, b));
+//!         *config.write().await = "slow";
+//!         assert_eq!(*config.read().await, "slow");
+//!     });
+//! }
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    future,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+    task::Poll,
+};
+
+use crate::{mutex, wake_list::WakeHandle};
+
+/// Unregisters a canceled lock, passing on its wakeup if woken
+struct Waiting<'a, T> {
+    state: &'a mutex::Mutex<T>,
+    wh: WakeHandle,
+    /// True for the send wake list, false for the receive wake list
+    send: bool,
+}
+
+impl<T> Drop for Waiting<'_, T> {
+    fn drop(&mut self) {
+        if self.send {
+            self.state.cancel_send(&mut self.wh);
+        } else {
+            self.state.cancel_recv(&mut self.wh);
+        }
+    }
+}
+
+/// An async mutual exclusion lock
+pub struct Mutex<T: ?Sized> {
+    /// True while locked
+    locked: mutex::Mutex<bool>,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    const_fn! {
+        /// Create a new unlocked mutex.
+        pub const fn new(data: T) -> Self {
+            Self {
+                locked: mutex::Mutex::new(false),
+                data: UnsafeCell::new(data),
+            }
+        }
+    }
+
+    /// Consume the mutex, returning the data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Wait until unlocked, then lock.
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        let mut waiting = Waiting {
+            state: &self.locked,
+            wh: WakeHandle::new(),
+            send: false,
+        };
+
+        future::poll_fn(|cx| {
+            self.locked.poll_recv(cx, &mut waiting.wh, |locked| {
+                if *locked {
+                    return Poll::Pending;
+                }
+
+                *locked = true;
+                Poll::Ready(())
+            })
+        })
+        .await;
+
+        MutexGuard(self, PhantomData)
+    }
+
+    /// Lock if unlocked, without waiting.
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        let locked =
+            self.locked.with(|locked| !core::mem::replace(locked, true));
+
+        locked.then_some(MutexGuard(self, PhantomData))
+    }
+
+    /// Get a mutable reference to the data, without locking.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Mutex").finish_non_exhaustive()
+    }
+}
+
+/// Access to the data of a locked [`Mutex`], unlocking when dropped
+#[must_use = "the mutex is unlocked immediately if not held"]
+pub struct MutexGuard<'a, T: ?Sized>(&'a Mutex<T>, PhantomData<&'a mut T>);
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safe because the mutex is locked while the guard exists
+        unsafe { &*self.0.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safe because the mutex is locked while the guard exists
+        unsafe { &mut *self.0.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.0.locked.with(|locked| *locked = false);
+        self.0.locked.wake_recv();
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MutexGuard").finish_non_exhaustive()
+    }
+}
+
+/// Readers and writer holding an [`RwLock`]
+struct Holders {
+    readers: usize,
+    writer: bool,
+}
+
+/// An async lock allowing either many readers or one writer
+///
+/// Readers wait on the receive wake list and writers on the send wake list,
+/// so releasing a write lock can wake every reader at once.
+pub struct RwLock<T: ?Sized> {
+    holders: mutex::Mutex<Holders>,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+impl<T> RwLock<T> {
+    const_fn! {
+        /// Create a new unlocked lock.
+        pub const fn new(data: T) -> Self {
+            Self {
+                holders: mutex::Mutex::new(Holders {
+                    readers: 0,
+                    writer: false,
+                }),
+                data: UnsafeCell::new(data),
+            }
+        }
+    }
+
+    /// Consume the lock, returning the data.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Wait until there is no writer, then lock for reading.
+    pub async fn read(&self) -> RwLockReadGuard<'_, T> {
+        let mut waiting = Waiting {
+            state: &self.holders,
+            wh: WakeHandle::new(),
+            send: false,
+        };
+
+        future::poll_fn(|cx| {
+            self.holders.poll_recv(cx, &mut waiting.wh, |holders| {
+                if holders.writer {
+                    return Poll::Pending;
+                }
+
+                holders.readers += 1;
+                Poll::Ready(())
+            })
+        })
+        .await;
+
+        RwLockReadGuard(self)
+    }
+
+    /// Wait until there are no readers or writer, then lock for writing.
+    pub async fn write(&self) -> RwLockWriteGuard<'_, T> {
+        let mut waiting = Waiting {
+            state: &self.holders,
+            wh: WakeHandle::new(),
+            send: true,
+        };
+
+        future::poll_fn(|cx| {
+            self.holders.poll_send(cx, &mut waiting.wh, |holders| {
+                if holders.writer || holders.readers > 0 {
+                    return Poll::Pending;
+                }
+
+                holders.writer = true;
+                Poll::Ready(())
+            })
+        })
+        .await;
+
+        RwLockWriteGuard(self)
+    }
+
+    /// Get a mutable reference to the data, without locking.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RwLock").finish_non_exhaustive()
+    }
+}
+
+/// Shared access to the data of an [`RwLock`], unlocking when dropped
+#[must_use = "the lock is released immediately if not held"]
+pub struct RwLockReadGuard<'a, T: ?Sized>(&'a RwLock<T>);
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safe because there is no writer while the guard exists
+        unsafe { &*self.0.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let last = self.0.holders.with(|holders| {
+            holders.readers -= 1;
+            holders.readers == 0
+        });
+
+        if last {
+            self.0.holders.wake_send();
+        }
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RwLockReadGuard").finish_non_exhaustive()
+    }
+}
+
+/// Exclusive access to the data of an [`RwLock`], unlocking when dropped
+#[must_use = "the lock is released immediately if not held"]
+pub struct RwLockWriteGuard<'a, T: ?Sized>(&'a RwLock<T>);
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safe because there are no other holders while the guard exists
+        unsafe { &*self.0.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safe because there are no other holders while the guard exists
+        unsafe { &mut *self.0.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.0.holders.with(|holders| holders.writer = false);
+        self.0.holders.wake_all_recv();
+        self.0.holders.wake_send();
+    }
+}
+
+impl<T: ?Sized> core::fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RwLockWriteGuard").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,104 @@
+use alloc::boxed::Box;
+use core::fmt::{Arguments, Debug};
+
+use crate::{atomic::Arc, Channel, RecvError, SendError};
+
+/// Shared logging sink
+type Sink = Arc<dyn Fn(Arguments<'_>) + Send + Sync>;
+
+/// A [`Channel`] wrapper that logs every message with [`Debug`]
+///
+/// Sent messages are logged before they are deposited, and received messages
+/// are logged after they are taken.  Logging only happens in builds with
+/// `debug_assertions` enabled, and compiles out otherwise.
+///
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+///
+/// use whisk::{Channel, Tap};
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let log = Arc::new(Mutex::new(Vec::new()));
+///         let tap = Tap::new(Channel::new(), {
+///             let log = log.clone();
+///
+///             move |args| log.lock().unwrap().push(args.to_string())
+///         });
+///
+///         tap.send(Some(7)).await.unwrap();
+///         assert_eq!(tap.recv().await, Ok(Some(7)));
+///
+///         if cfg!(debug_assertions) {
+///             assert_eq!(
+///                 *log.lock().unwrap(),
+///                 ["send: Some(7)", "recv: Some(7)"],
+///             );
+///         }
+///     });
+/// }
+/// ```
+pub struct Tap<T: Debug, U: ?Sized = ()> {
+    channel: Channel<T, U>,
+    sink: Sink,
+}
+
+impl<T: Debug, U: ?Sized> Tap<T, U> {
+    /// Wrap a channel, logging messages to `sink`.
+    pub fn new(
+        channel: Channel<T, U>,
+        sink: impl Fn(Arguments<'_>) + Send + Sync + 'static,
+    ) -> Self {
+        // Boxed first, since unsizing portable-atomic's `Arc` isn't stable
+        let sink: Box<dyn Fn(Arguments<'_>) + Send + Sync> = Box::new(sink);
+
+        Self {
+            channel,
+            sink: Arc::from(sink),
+        }
+    }
+
+    /// Log and send a message on the wrapped channel.
+    pub async fn send(&self, message: T) -> Result<(), SendError<T>> {
+        #[cfg(debug_assertions)]
+        (self.sink)(format_args!("send: {message:?}"));
+
+        self.channel.send(message).await
+    }
+
+    /// Receive and log a message from the wrapped channel.
+    pub async fn recv(&self) -> Result<T, RecvError> {
+        let message = self.channel.recv().await?;
+
+        #[cfg(debug_assertions)]
+        (self.sink)(format_args!("recv: {message:?}"));
+
+        Ok(message)
+    }
+
+    /// Get the wrapped channel.
+    pub fn channel(&self) -> &Channel<T, U> {
+        &self.channel
+    }
+
+    /// Unwrap the channel, removing the logging.
+    pub fn into_inner(self) -> Channel<T, U> {
+        self.channel
+    }
+}
+
+impl<T: Debug, U: ?Sized> Clone for Tap<T, U> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel.clone(),
+            sink: Arc::clone(&self.sink),
+        }
+    }
+}
+
+impl<T: Debug, U: ?Sized> Debug for Tap<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Tap").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,87 @@
+//! Hooks for deterministic testing of concurrent interleavings
+//!
+//! A yield point callback runs on the polling thread at the critical point
+//! inside channel polling, after a task finds it can't make progress and right
+//! before it registers to be woken.  Running another operation from within
+//! the callback forces it to happen exactly in that window.
+//!
+//! The callback runs while the polling task still holds the channel's lock,
+//! since registering under the lock is what keeps wakeups from being lost.
+//! Operations on the same channel from within the callback find it locked,
+//! so they take the contended path: they register to be woken and return
+//! `Pending` without making progress, then must be woken once the lock is
+//! released.  This can test that contended operations are never stranded,
+//! but can't test an operation completing between the failed check and
+//! registration, since there is no such window.
+//!
+//! ```rust
+//! use std::{cell::RefCell, future::Future, pin::Pin, rc::Rc, task::Context};
+//!
+//! use futures::task::noop_waker_ref;
+//! use whisk::{test_util, Queue};
+//!
+//! static QUEUE: Queue<u32> = Queue::new();
+//!
+//! let mut cx = Context::from_waker(noop_waker_ref());
+//! let mut receiving = QUEUE.recv();
+//! let sending = Rc::new(RefCell::new(QUEUE.send(7)));
+//!
+//! // Run the sender while the receiver holds the lock, about to park
+//! test_util::set_yield_point({
+//!     let sending = sending.clone();
+//!
+//!     move || {
+//!         let mut cx = Context::from_waker(noop_waker_ref());
+//!         let sending = &mut *sending.borrow_mut();
+//!
+//!         // Contended, so the sender registers instead of sending
+//!         assert!(Pin::new(sending).poll(&mut cx).is_pending());
+//!     }
+//! });
+//! assert!(Pin::new(&mut receiving).poll(&mut cx).is_pending());
+//! test_util::clear_yield_point();
+//!
+//! // Neither side loses the message
+//! let sending = &mut *sending.borrow_mut();
+//!
+//! assert!(Pin::new(sending).poll(&mut cx).is_ready());
+//! assert_eq!(Pin::new(&mut receiving).poll(&mut cx), Ok(7).into());
+//! ```
+
+use alloc::boxed::Box;
+use core::cell::Cell;
+
+std::thread_local! {
+    static YIELD_POINT: Cell<Option<Box<dyn Fn()>>> = const { Cell::new(None) };
+}
+
+/// Set the yield point callback for the current thread.
+pub fn set_yield_point(f: impl Fn() + 'static) {
+    YIELD_POINT.with(|yield_point| yield_point.set(Some(Box::new(f))));
+}
+
+/// Remove the yield point callback for the current thread.
+pub fn clear_yield_point() {
+    YIELD_POINT.with(|yield_point| yield_point.set(None));
+}
+
+/// Run the yield point callback, if set
+///
+/// The callback is removed while running, so channel operations within it
+/// don't recurse.
+pub(crate) fn yield_point() {
+    let Some(f) = YIELD_POINT.with(Cell::take) else {
+        return;
+    };
+
+    f();
+
+    YIELD_POINT.with(|yield_point| {
+        // Don't overwrite if callback was changed from within
+        if let Some(new) = yield_point.take() {
+            yield_point.set(Some(new));
+        } else {
+            yield_point.set(Some(f));
+        }
+    });
+}
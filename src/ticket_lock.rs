@@ -0,0 +1,58 @@
+use crate::atomic::{
+    AtomicUsize,
+    Ordering::{Acquire, Relaxed, Release},
+};
+
+/// Fair spinlock, where waiters are served in the order they arrived
+///
+/// Each waiter takes a ticket, and spins until its number is served, so no
+/// thread can be starved by others repeatedly winning the lock.
+pub(crate) struct TicketLock {
+    /// Next ticket to hand out
+    next: AtomicUsize,
+    /// Ticket currently allowed to hold the lock
+    serving: AtomicUsize,
+}
+
+impl TicketLock {
+    /// Create a new unlocked lock
+    pub(crate) const fn new() -> Self {
+        Self {
+            next: AtomicUsize::new(0),
+            serving: AtomicUsize::new(0),
+        }
+    }
+
+    /// Try to acquire the lock, returning true if acquired
+    ///
+    /// Only takes a ticket if it would be served immediately, so there's
+    /// never a ticket left waiting.
+    pub(crate) fn try_lock(&self) -> bool {
+        let serving = self.serving.load(Acquire);
+
+        self.next
+            .compare_exchange(
+                serving,
+                serving.wrapping_add(1),
+                Relaxed,
+                Relaxed,
+            )
+            .is_ok()
+    }
+
+    /// Wait until the lock is acquired
+    pub(crate) fn lock(&self) {
+        let ticket = self.next.fetch_add(1, Relaxed);
+        let mut attempt = 0;
+
+        while self.serving.load(Acquire) != ticket {
+            crate::backoff::backoff(attempt);
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    /// Release the lock, serving the next ticket
+    pub(crate) fn unlock(&self) {
+        self.serving.fetch_add(1, Release);
+    }
+}
@@ -0,0 +1,137 @@
+use core::{future::Future, time::Duration};
+
+use crate::{Channel, Queue, Recv, RecvError, Send, SendError};
+
+mod sealed {
+    use crate::{Channel, Queue};
+
+    pub trait Sealed {}
+
+    impl<T, U: ?Sized> Sealed for Channel<T, U> {}
+    impl<T, U: ?Sized> Sealed for Queue<T, U> {}
+}
+
+/// Sending half of any whisk channel
+///
+/// This trait is sealed, and can be used to write code that is generic over
+/// whisk channel types.
+///
+/// ```rust
+/// use whisk::{AsyncSender, Channel, Queue};
+///
+/// async fn send_twice(sender: &impl AsyncSender<u32>) {
+///     sender.send(1).await.unwrap();
+///     sender.send(2).await.unwrap();
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let channel = Channel::new();
+///         let task = async {
+///             let first = channel.recv().await.unwrap();
+///
+///             (first, channel.recv().await.unwrap())
+///         };
+///         let (_, received) =
+///             futures::future::join(send_twice(&channel), task).await;
+///
+///         assert_eq!(received, (1, 2));
+///
+///         let queue = Queue::new();
+///         let task = async {
+///             let first = queue.recv().await.unwrap();
+///
+///             (first, queue.recv().await.unwrap())
+///         };
+///         let (_, received) =
+///             futures::future::join(send_twice(&queue), task).await;
+///
+///         assert_eq!(received, (1, 2));
+///     });
+/// }
+/// ```
+pub trait AsyncSender<T>: sealed::Sealed {
+    /// Future returned from [`AsyncSender::send()`]
+    type Send<'a>: Future<Output = Result<(), SendError<T>>>
+    where
+        Self: 'a;
+
+    /// Send a message on this channel.
+    fn send(&self, message: T) -> Self::Send<'_>;
+}
+
+/// Receiving half of any whisk channel
+///
+/// This trait is sealed, and can be used to write code that is generic over
+/// whisk channel types.
+pub trait AsyncReceiver<T>: sealed::Sealed {
+    /// Future returned from [`AsyncReceiver::recv()`]
+    type Recv<'a>: Future<Output = Result<T, RecvError>>
+    where
+        Self: 'a;
+
+    /// Receive a message from this channel.
+    fn recv(&self) -> Self::Recv<'_>;
+}
+
+/// Source of timers for timeouts, keeping whisk executor-agnostic
+///
+/// Implemented for functions from a [`Duration`] to a future, such as an
+/// async runtime's `sleep()`.
+pub trait Sleep {
+    /// Future returned from [`Sleep::sleep()`]
+    type Timer: Future<Output = ()>;
+
+    /// Create a timer that completes once `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> Self::Timer;
+}
+
+impl<F, S> Sleep for F
+where
+    F: Fn(Duration) -> S,
+    S: Future<Output = ()>,
+{
+    type Timer = S;
+
+    #[inline(always)]
+    fn sleep(&self, duration: Duration) -> S {
+        self(duration)
+    }
+}
+
+impl<T, U: ?Sized> AsyncSender<T> for Queue<T, U> {
+    type Send<'a> = Send<'a, T, U> where Self: 'a;
+
+    #[inline(always)]
+    fn send(&self, message: T) -> Self::Send<'_> {
+        Queue::send(self, message)
+    }
+}
+
+impl<T, U: ?Sized> AsyncReceiver<T> for Queue<T, U> {
+    type Recv<'a> = Recv<'a, T, U> where Self: 'a;
+
+    #[inline(always)]
+    fn recv(&self) -> Self::Recv<'_> {
+        Queue::recv(self)
+    }
+}
+
+impl<T, U: ?Sized> AsyncSender<T> for Channel<T, U> {
+    type Send<'a> = Send<'a, T, U> where Self: 'a;
+
+    #[inline(always)]
+    fn send(&self, message: T) -> Self::Send<'_> {
+        self.0.send(message)
+    }
+}
+
+impl<T, U: ?Sized> AsyncReceiver<T> for Channel<T, U> {
+    type Recv<'a> = Recv<'a, T, U> where Self: 'a;
+
+    #[inline(always)]
+    fn recv(&self) -> Self::Recv<'_> {
+        self.0.recv()
+    }
+}
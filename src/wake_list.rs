@@ -1,15 +1,19 @@
+#[cfg(not(feature = "fixed_wakers"))]
 use alloc::boxed::Box;
 use core::{
     cell::UnsafeCell,
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
     ptr,
-    sync::atomic::{
-        AtomicPtr, AtomicUsize,
-        Ordering::{Relaxed, SeqCst},
-    },
     task::Waker,
 };
 
+#[cfg(not(feature = "fixed_wakers"))]
+use crate::atomic::{checked::AtomicPtr, Ordering::Acquire};
+use crate::atomic::{
+    checked::AtomicUsize,
+    Ordering::{Relaxed, Release, SeqCst},
+};
+
 /// Status of wake node
 #[repr(usize)]
 enum WakeState {
@@ -53,7 +57,7 @@ impl WakeNode {
     /// Register a new waker
     ///
     /// Slots can be Empty, Ready or Waking (If Waking, wakes immediately)
-    fn register(&self, waker: Waker) {
+    fn register(&self, waker: &Waker) {
         // Attempt to clear first slot and begin registering
         let r = self
             .state
@@ -74,13 +78,18 @@ impl WakeNode {
         // Set waker and mark ready
         match r {
             Ok(prev) => {
-                // Drop before overwriting
-                if prev == WakeState::Ready as usize {
-                    unsafe { (*self.waker.get()).assume_init_drop() }
-                }
+                let stored = unsafe { &mut *self.waker.get() };
 
-                // Use first waker slot and set to ready for waking
-                unsafe { *self.waker.get() = MaybeUninit::new(waker) };
+                // Keep the previous waker if it wakes the same task, which is
+                // the common case of repeated polling, avoiding a clone
+                if prev != WakeState::Ready as usize {
+                    *stored = MaybeUninit::new(waker.clone());
+                } else if !unsafe { stored.assume_init_ref() }.will_wake(waker)
+                {
+                    // Drop before overwriting
+                    unsafe { stored.assume_init_drop() };
+                    *stored = MaybeUninit::new(waker.clone());
+                }
 
                 // Finish, checking if canceled
                 let r =
@@ -102,7 +111,7 @@ impl WakeNode {
                     self.state.store(WakeState::Empty as usize, SeqCst);
                 }
             }
-            Err(_) => waker.wake(),
+            Err(_) => waker.wake_by_ref(),
         }
     }
 
@@ -148,17 +157,25 @@ impl WakeNode {
     }
 
     /// Free this wake node (must be done on registration task)
-    fn free(&self) {
+    ///
+    /// Returns true if the node was woken since it was last registered.
+    fn free(&self) -> bool {
         match self.state.swap(WakeState::Freeing as usize, SeqCst) {
-            x if x == WakeState::Empty as usize => {}
+            x if x == WakeState::Empty as usize => {
+                // Publish to `allocate()`, which pairs with its compare
+                // exchange
+                self.state.store(WakeState::Garbage as usize, Release);
+                true
+            }
             x if x == WakeState::Ready as usize => {
                 unsafe { (*self.waker.get()).assume_init_drop() };
+                self.state.store(WakeState::Garbage as usize, Release);
+                false
             }
-            x if x == WakeState::Waking as usize => return,
+            // Becomes garbage once waking finishes
+            x if x == WakeState::Waking as usize => true,
             _ => unreachable!(),
         }
-
-        self.state.store(WakeState::Garbage as usize, SeqCst);
     }
 }
 
@@ -175,32 +192,59 @@ impl WakeHandle {
     }
 
     /// Register a waker
-    pub(crate) fn register(&mut self, wl: &WakeList, waker: Waker) {
+    pub(crate) fn register(&mut self, wl: &WakeList, waker: &Waker) {
         // Allocate a waker if needed
         if self.0.is_null() {
-            self.0 = wl.allocate();
+            let Some(node) = wl.allocate() else {
+                // Every wake node is in use, so poll again rather than sleep
+                waker.wake_by_ref();
+                return;
+            };
+
+            self.0 = node;
         }
 
         // Register the waker
         unsafe { (*self.0).register(waker) }
     }
+
+    /// Unregister, returning true if woken since last registered
+    ///
+    /// A task that is woken, but then canceled before it is polled, should
+    /// pass the wakeup on to another task waiting on the same list.
+    pub(crate) fn cancel(&mut self) -> bool {
+        let node = mem::replace(&mut self.0, ptr::null());
+
+        !node.is_null() && unsafe { (*node).free() }
+    }
 }
 
 impl Drop for WakeHandle {
     fn drop(&mut self) {
         // Unregister the waker if set
-        if !self.0.is_null() {
-            unsafe { (*self.0).free() }
-        }
+        self.cancel();
     }
 }
 
+#[cfg(not(feature = "fixed_wakers"))]
 struct Node<T> {
     next: AtomicPtr<Node<T>>,
     data: T,
 }
 
 /// A `WakeList` stores an append-only atomic linked list of wakers
+///
+/// Nodes are only allocated when every existing node is in use, and are
+/// reused after their [`WakeHandle`] is dropped, so the hot path doesn't
+/// allocate, and unregistering is a single state swap.
+///
+/// Nodes don't live inside the waiting futures (an intrusive list), since a
+/// node linked into the list can't move, which would require pinning.
+/// [`Send`](crate::Send) and [`Recv`](crate::Recv) are public `Unpin`
+/// futures, polled by `&mut` reference in loops and `select!`, and the
+/// channel handles' `poll_send()` and `poll_recv()` take `&mut self`, so
+/// making them `!Unpin` would break both.
+#[cfg(not(feature = "fixed_wakers"))]
 pub(crate) struct WakeList {
     // Root node of list of wakers
     root: AtomicPtr<Node<WakeNode>>,
@@ -208,6 +252,7 @@ pub(crate) struct WakeList {
     next: AtomicPtr<Node<WakeNode>>,
 }
 
+#[cfg(not(feature = "fixed_wakers"))]
 impl Drop for WakeList {
     fn drop(&mut self) {
         let mut tmp = self.root.load(Relaxed);
@@ -219,12 +264,15 @@ impl Drop for WakeList {
     }
 }
 
+#[cfg(not(feature = "fixed_wakers"))]
 impl WakeList {
-    /// Create a new empty wake list
-    pub(crate) const fn new() -> Self {
-        Self {
-            root: AtomicPtr::new(ptr::null_mut()),
-            next: AtomicPtr::new(ptr::null_mut()),
+    const_fn! {
+        /// Create a new empty wake list
+        pub(crate) const fn new() -> Self {
+            Self {
+                root: AtomicPtr::new(ptr::null_mut()),
+                next: AtomicPtr::new(ptr::null_mut()),
+            }
         }
     }
 
@@ -232,13 +280,14 @@ impl WakeList {
     ///
     /// If no wakers are registered, doesn't do anything.
     pub(crate) fn wake_one(&self) {
-        // Start from next pointer into list
-        let next = self.next.load(SeqCst);
+        // Start from next pointer into list (only a fairness hint, so doesn't
+        // need to be sequentially consistent)
+        let next = self.next.load(Acquire);
         let mut tmp = next;
         while !tmp.is_null() {
             let next = unsafe { (*tmp).next.load(Relaxed) };
             if unsafe { (*tmp).data.wake().is_ok() } {
-                self.next.store(next, SeqCst);
+                self.next.store(next, Release);
                 return;
             }
             tmp = next;
@@ -249,20 +298,29 @@ impl WakeList {
         while tmp != next {
             let next = unsafe { (*tmp).next.load(Relaxed) };
             if unsafe { (*tmp).data.wake().is_ok() } {
-                self.next.store(next, SeqCst);
+                self.next.store(next, Release);
                 return;
             }
             tmp = next;
         }
     }
 
+    /// Attempt to wake all registered wakers.
+    pub(crate) fn wake_all(&self) {
+        let mut tmp = self.root.load(SeqCst);
+        while !tmp.is_null() {
+            let _ = unsafe { (*tmp).data.wake() };
+            tmp = unsafe { (*tmp).next.load(Relaxed) };
+        }
+    }
+
     /// Allocate a new `WakeNode`
-    fn allocate(&self) -> *const WakeNode {
+    fn allocate(&self) -> Option<*const WakeNode> {
         // Go through list to see if unused existing allocation to use
         let mut tmp = self.root.load(SeqCst);
         while !tmp.is_null() {
             if let Ok(wn) = unsafe { (*tmp).data.allocate() } {
-                return wn;
+                return Some(wn);
             }
             tmp = unsafe { (*tmp).next.load(Relaxed) };
         }
@@ -281,6 +339,107 @@ impl WakeList {
             root = r;
             unsafe { (*node).next = AtomicPtr::new(root) };
         }
-        unsafe { &(*node).data }
+        Some(unsafe { &(*node).data })
+    }
+}
+
+/// Number of wakers each fixed wake list can hold, set at build time with the
+/// `WHISK_FIXED_WAKERS` environment variable
+#[cfg(feature = "fixed_wakers")]
+const FIXED_WAKERS: usize = match option_env!("WHISK_FIXED_WAKERS") {
+    Some(wakers) => parse_wakers(wakers),
+    None => 16,
+};
+
+/// Parse the number of fixed wakers at compile time
+#[cfg(feature = "fixed_wakers")]
+const fn parse_wakers(wakers: &str) -> usize {
+    let digits = wakers.as_bytes();
+    let mut wakers = 0;
+    let mut i = 0;
+
+    while i < digits.len() {
+        assert!(
+            digits[i].is_ascii_digit(),
+            "WHISK_FIXED_WAKERS must be a number",
+        );
+        wakers = wakers * 10 + (digits[i] - b'0') as usize;
+        i += 1;
+    }
+
+    assert!(wakers != 0, "WHISK_FIXED_WAKERS must be at least one");
+    wakers
+}
+
+/// A `WakeList` stores a fixed array of `N` wakers, without allocating
+#[cfg(feature = "fixed_wakers")]
+pub(crate) struct WakeList<const N: usize = FIXED_WAKERS> {
+    // Wake nodes
+    nodes: [WakeNode; N],
+    // Index of next one to try waking ("fairness" mechanism)
+    next: AtomicUsize,
+}
+
+// Nodes are only accessed through their atomic state
+#[cfg(feature = "fixed_wakers")]
+unsafe impl<const N: usize> Send for WakeList<N> {}
+#[cfg(feature = "fixed_wakers")]
+unsafe impl<const N: usize> Sync for WakeList<N> {}
+
+#[cfg(feature = "fixed_wakers")]
+impl<const N: usize> WakeList<N> {
+    const_fn! {
+        /// Create a new empty wake list
+        pub(crate) const fn new() -> Self {
+            // Only used to initialize the array, never shared
+            #[cfg(not(loom))]
+            #[allow(clippy::declare_interior_mutable_const)]
+            const GARBAGE: WakeNode = WakeNode {
+                state: AtomicUsize::new(WakeState::Garbage as usize),
+                waker: UnsafeCell::new(MaybeUninit::uninit()),
+            };
+
+            #[cfg(not(loom))]
+            let nodes = [GARBAGE; N];
+            #[cfg(loom)]
+            let nodes = core::array::from_fn(|_| WakeNode {
+                state: AtomicUsize::new(WakeState::Garbage as usize),
+                waker: UnsafeCell::new(MaybeUninit::uninit()),
+            });
+
+            Self {
+                nodes,
+                next: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    /// Attempt to wake one waker.
+    ///
+    /// If no wakers are registered, doesn't do anything.
+    pub(crate) fn wake_one(&self) {
+        // Only a fairness hint, so doesn't need to be sequentially consistent
+        let start = self.next.load(Relaxed);
+
+        for i in 0..N {
+            let index = (start + i) % N;
+
+            if self.nodes[index].wake().is_ok() {
+                self.next.store((index + 1) % N, Relaxed);
+                return;
+            }
+        }
+    }
+
+    /// Attempt to wake all registered wakers.
+    pub(crate) fn wake_all(&self) {
+        for node in &self.nodes {
+            let _ = node.wake();
+        }
+    }
+
+    /// Allocate an unused `WakeNode`, if any
+    fn allocate(&self) -> Option<*const WakeNode> {
+        self.nodes.iter().find_map(|node| node.allocate().ok())
     }
 }
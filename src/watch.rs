@@ -0,0 +1,159 @@
+//! Watch channels holding only the most recent value
+//!
+//! A [`Channel`] always stores the latest value sent on it.  Reading doesn't
+//! consume the value, and each handle can wait for the value to change since
+//! it last looked, which makes it a good fit for configuration and progress.
+//!
+//! ```rust
+//! use whisk::watch::Channel;
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         let volume = Channel::new(5);
+//!         let mut watcher = volume.clone();
+//!
+//!         // Only the latest value is kept
+//!         volume.send(7).unwrap();
+//!         volume.send(9).unwrap();
+//!         watcher.changed().await.unwrap();
+//!         assert_eq!(watcher.borrow(|volume| *volume), 9);
+//!         assert!(!watcher.has_changed());
+//!
+//!         // Wait for the next change
+//!         let sending = async { volume.send(3).unwrap() };
+//!         let (changed, _) =
+//!             futures::future::join(watcher.changed(), sending).await;
+//!
+//!         changed.unwrap();
+//!         assert_eq!(watcher.borrow(|volume| *volume), 3);
+//!
+//!         volume.close();
+//!         assert!(watcher.changed().await.is_err());
+//!     });
+//! }
+//! ```
+
+use core::{future, mem, task::Poll};
+
+use crate::{
+    atomic::Arc, mutex::Mutex, wake_list::WakeHandle, RecvError, SendError,
+};
+
+/// Value protected by the mutex
+struct Locked<T> {
+    /// The most recent value
+    value: T,
+    /// Number of times the value has been sent
+    version: u64,
+    /// True if the channel has been closed
+    closed: bool,
+}
+
+/// A channel that stores only the most recent value sent on it
+///
+/// Each clone tracks which version of the value it has seen.
+pub struct Channel<T> {
+    shared: Arc<Mutex<Locked<T>>>,
+    /// Version of the value last seen by this handle
+    seen: u64,
+}
+
+impl<T> Channel<T> {
+    /// Create a new watch channel with an initial value, which is considered
+    /// already seen.
+    pub fn new(value: T) -> Self {
+        Self {
+            shared: Arc::new(Mutex::new(Locked {
+                value,
+                version: 0,
+                closed: false,
+            })),
+            seen: 0,
+        }
+    }
+
+    /// Replace the value, notifying every handle waiting on a change.
+    ///
+    /// Returns the value back as an error if the channel is closed.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let old = self.shared.with(|locked| {
+            if locked.closed {
+                return Err(SendError(value));
+            }
+
+            locked.version += 1;
+            Ok(mem::replace(&mut locked.value, value))
+        })?;
+
+        // Drop the old value outside of the lock
+        drop(old);
+        self.shared.wake_all_recv();
+        Ok(())
+    }
+
+    /// Read the current value with `f`, without marking it as seen.
+    ///
+    /// The channel is locked while `f` runs, so it should be quick.
+    pub fn borrow<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.shared.with(|locked| f(&locked.value))
+    }
+
+    /// Check if the value has changed since this handle last saw it.
+    pub fn has_changed(&self) -> bool {
+        self.shared.with(|locked| locked.version != self.seen)
+    }
+
+    /// Wait for the value to change since this handle last saw it, then mark
+    /// it as seen.
+    ///
+    /// Returns an error once the channel is closed and the latest value has
+    /// been seen.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        let mut wh = WakeHandle::new();
+        let Self { shared, seen } = self;
+
+        future::poll_fn(|cx| {
+            shared.poll_recv(cx, &mut wh, |locked| {
+                if locked.version != *seen {
+                    *seen = locked.version;
+                    Poll::Ready(Ok(()))
+                } else if locked.closed {
+                    Poll::Ready(Err(RecvError))
+                } else {
+                    Poll::Pending
+                }
+            })
+        })
+        .await
+    }
+
+    /// Close the channel, causing further sends to fail, and waking every
+    /// handle waiting on a change.
+    pub fn close(&self) {
+        self.shared.with(|locked| locked.closed = true);
+        self.shared.wake_all_recv();
+    }
+
+    /// Check if the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        self.shared.with(|locked| locked.closed)
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+            seen: self.seen,
+        }
+    }
+}
+
+impl<T> core::fmt::Debug for Channel<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Channel")
+            .field("seen", &self.seen)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,193 @@
+//! Weighted fair queuing channels for sharing a receiver between sender
+//! classes
+//!
+//! A [`Channel`] has a fixed set of sender classes, each with a FIFO and a
+//! weight.  Receiving serves the classes with deficit round robin, so each
+//! class gets a share of the messages proportional to its weight, and a busy
+//! class can't starve the others.
+//!
+//! ```rust
+//! use whisk::wfq::Channel;
+//!
+//! # #[ntest::timeout(1000)]
+//! fn main() {
+//!     pasts::Executor::default().block_on(async {
+//!         // Tenant 0 gets three messages for every one of tenant 1
+//!         let channel = Channel::new(&[3, 1]);
+//!
+//!         for i in 0..6 {
+//!             channel.send_class(0, ("noisy", i));
+//!         }
+//!         channel.send_class(1, ("quiet", 0));
+//!         channel.send_class(1, ("quiet", 1));
+//!
+//!         let mut received = Vec::new();
+//!
+//!         for _ in 0..8 {
+//!             received.push(channel.recv().await);
+//!         }
+//!
+//!         assert_eq!(
+//!             received,
+//!             [
+//!                 ("noisy", 0),
+//!                 ("noisy", 1),
+//!                 ("noisy", 2),
+//!                 ("quiet", 0),
+//!                 ("noisy", 3),
+//!                 ("noisy", 4),
+//!                 ("noisy", 5),
+//!                 ("quiet", 1),
+//!             ],
+//!         );
+//!     });
+//! }
+//! ```
+
+use alloc::{boxed::Box, collections::VecDeque};
+use core::{future, task::Poll};
+
+use crate::{atomic::Arc, mutex::Mutex, wake_list::WakeHandle};
+
+/// One sender class
+struct Class<T> {
+    /// FIFO of messages from this class
+    queue: VecDeque<T>,
+    /// Messages this class may receive per round
+    weight: usize,
+    /// Messages remaining in this class's current turn
+    deficit: usize,
+}
+
+/// Classes protected by the mutex
+struct Locked<T> {
+    /// Sender classes
+    classes: Box<[Class<T>]>,
+    /// Index of the class whose turn it is
+    current: usize,
+}
+
+impl<T> Locked<T> {
+    /// Move on to the next class's turn
+    fn advance(&mut self) {
+        self.classes[self.current].deficit = 0;
+        self.current = (self.current + 1) % self.classes.len();
+    }
+
+    /// Take the next message in deficit round robin order
+    fn pop(&mut self) -> Option<T> {
+        if self.classes.iter().all(|class| class.queue.is_empty()) {
+            return None;
+        }
+
+        loop {
+            let class = &mut self.classes[self.current];
+
+            if class.queue.is_empty() {
+                self.advance();
+                continue;
+            }
+
+            // Start of this class's turn
+            if class.deficit == 0 {
+                class.deficit = class.weight;
+            }
+
+            let message = class.queue.pop_front();
+
+            class.deficit -= 1;
+
+            if class.deficit == 0 || class.queue.is_empty() {
+                self.advance();
+            }
+
+            return message;
+        }
+    }
+}
+
+/// Unregisters a receive when dropped, passing on a wakeup it didn't handle
+struct Receiving<'a, T>(&'a Mutex<Locked<T>>, WakeHandle);
+
+impl<T> Drop for Receiving<'_, T> {
+    fn drop(&mut self) {
+        self.0.cancel_recv(&mut self.1);
+    }
+}
+
+/// An MPMC channel with weighted fair queuing between sender classes
+///
+/// Classes are unbounded, so sending never waits.
+pub struct Channel<T>(Arc<Mutex<Locked<T>>>);
+
+impl<T> Channel<T> {
+    /// Create a new channel with one sender class for each weight.
+    ///
+    /// # Panics
+    ///
+    /// If `weights` is empty, or contains zero.
+    pub fn new(weights: &[usize]) -> Self {
+        assert!(!weights.is_empty(), "Channel must have at least one class");
+        assert!(
+            weights.iter().all(|&weight| weight != 0),
+            "Class weights must be nonzero",
+        );
+
+        let classes = weights
+            .iter()
+            .map(|&weight| Class {
+                queue: VecDeque::new(),
+                weight,
+                deficit: 0,
+            })
+            .collect();
+
+        Self(Arc::new(Mutex::new(Locked {
+            classes,
+            current: 0,
+        })))
+    }
+
+    /// Get the number of sender classes in this channel.
+    pub fn classes(&self) -> usize {
+        self.0.with(|locked| locked.classes.len())
+    }
+
+    /// Send a message as the specified class on this channel.
+    ///
+    /// # Panics
+    ///
+    /// If `class` is out of range.
+    pub fn send_class(&self, class: usize, message: T) {
+        self.0
+            .with(|locked| locked.classes[class].queue.push_back(message));
+        self.0.wake_recv();
+    }
+
+    /// Receive the next message from this channel, in weighted fair order.
+    ///
+    /// If canceled after being woken for a message, another waiting receiver
+    /// is woken instead.
+    pub async fn recv(&self) -> T {
+        let mut receiving = Receiving(&self.0, WakeHandle::new());
+
+        future::poll_fn(|cx| {
+            self.0.poll_recv(cx, &mut receiving.1, |locked| {
+                locked.pop().map_or(Poll::Pending, Poll::Ready)
+            })
+        })
+        .await
+    }
+}
+
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> core::fmt::Debug for Channel<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,205 @@
+use core::{
+    future,
+    ops::{Deref, DerefMut},
+    task::Poll,
+};
+
+use crate::{
+    atomic::{Arc, AtomicUsize, Ordering::SeqCst},
+    wake_list::{WakeHandle, WakeList},
+    Queue,
+};
+
+struct Shared<T> {
+    /// Work in transit
+    queue: Queue<T>,
+    /// Number of dispatched work items that haven't been completed
+    outstanding: AtomicUsize,
+    /// List of waiting joiners
+    joiners: WakeList,
+}
+
+impl<T> Shared<T> {
+    /// Mark one work item as completed
+    fn complete(&self) {
+        if self.outstanding.fetch_sub(1, SeqCst) == 1 {
+            self.joiners.wake_all();
+        }
+    }
+}
+
+/// Decrements outstanding work when dropped, if dispatch is canceled
+struct Dispatching<'a, T>(&'a Shared<T>);
+
+impl<T> Drop for Dispatching<'_, T> {
+    fn drop(&mut self) {
+        self.0.complete();
+    }
+}
+
+/// A work queue distributing work between multiple workers, with completion
+/// tracking
+///
+/// Each call to [`join()`](WorkQueue::join) waits until all work dispatched
+/// so far has been completed.  Work dispatched after `join()` resolves starts
+/// a new round.
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicU32, Ordering};
+///
+/// use whisk::WorkQueue;
+///
+/// async fn worker(queue: WorkQueue<u32>, sum: &AtomicU32) {
+///     for _ in 0..2 {
+///         let work = queue.recv().await;
+///
+///         sum.fetch_add(*work, Ordering::Relaxed);
+///         work.complete();
+///     }
+/// }
+///
+/// # #[ntest::timeout(1000)]
+/// fn main() {
+///     pasts::Executor::default().block_on(async {
+///         let queue = WorkQueue::new();
+///         let sum = AtomicU32::new(0);
+///         let tasker = async {
+///             for i in 1..=4 {
+///                 queue.dispatch(i).await;
+///             }
+///
+///             queue.join().await;
+///             assert_eq!(queue.outstanding(), 0);
+///             assert_eq!(sum.load(Ordering::Relaxed), 10);
+///         };
+///
+///         futures::join!(
+///             tasker,
+///             worker(queue.clone(), &sum),
+///             worker(queue.clone(), &sum),
+///         );
+///     });
+/// }
+/// ```
+pub struct WorkQueue<T>(Arc<Shared<T>>);
+
+impl<T> WorkQueue<T> {
+    /// Create a new work queue.
+    pub fn new() -> Self {
+        Self(Arc::new(Shared {
+            queue: Queue::new(),
+            outstanding: AtomicUsize::new(0),
+            joiners: WakeList::new(),
+        }))
+    }
+
+    /// Dispatch work to be received by a worker.
+    pub async fn dispatch(&self, work: T) {
+        self.0.outstanding.fetch_add(1, SeqCst);
+
+        let dispatching = Dispatching(&self.0);
+
+        if self.0.queue.send(work).await.is_err() {
+            unreachable!("work queue is never closed");
+        }
+
+        core::mem::forget(dispatching);
+    }
+
+    /// Receive work, as a worker.
+    ///
+    /// The work is marked completed when the returned [`Work`] is dropped.
+    pub async fn recv(&self) -> Work<T> {
+        let Ok(work) = self.0.queue.recv().await else {
+            unreachable!("work queue is never closed");
+        };
+
+        Work {
+            work,
+            shared: self.0.clone(),
+        }
+    }
+
+    /// Get the number of dispatched work items that haven't been completed.
+    pub fn outstanding(&self) -> usize {
+        self.0.outstanding.load(SeqCst)
+    }
+
+    /// Wait until all dispatched work has been completed.
+    pub async fn join(&self) {
+        let mut wh = WakeHandle::new();
+
+        future::poll_fn(|cx| {
+            if self.outstanding() == 0 {
+                return Poll::Ready(());
+            }
+
+            wh.register(&self.0.joiners, cx.waker());
+
+            // Check again in case of completion before registering
+            if self.outstanding() == 0 {
+                return Poll::Ready(());
+            }
+
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<T> Clone for WorkQueue<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> core::fmt::Debug for WorkQueue<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("WorkQueue")
+            .field("outstanding", &self.outstanding())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Default for WorkQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Work received from a [`WorkQueue`], completed when dropped
+pub struct Work<T> {
+    work: T,
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Work<T> {
+    /// Mark this work as completed.
+    pub fn complete(self) {}
+}
+
+impl<T> Deref for Work<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.work
+    }
+}
+
+impl<T> DerefMut for Work<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.work
+    }
+}
+
+impl<T> Drop for Work<T> {
+    fn drop(&mut self) {
+        self.shared.complete();
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for Work<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Work").field(&self.work).finish()
+    }
+}
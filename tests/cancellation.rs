@@ -0,0 +1,84 @@
+//! Cancels a receive after it is woken for a message, but before it is
+//! polled, asserting that the wakeup is passed on to another waiting
+//! receive rather than lost.
+
+mod harness;
+
+use std::future::Future;
+
+use harness::Task;
+use whisk::{lanes, priority, wfq, Channel};
+
+/// Start two receives with `recv`, send a message with `send`, then cancel
+/// whichever receive was woken
+fn cancel_woken<C, F>(name: &str, channel: C, recv: fn(C) -> F, send: fn(&C))
+where
+    C: Clone,
+    F: Future<Output = ()> + 'static,
+{
+    let mut first = Task::new(recv(channel.clone()));
+    let mut second = Task::new(recv(channel.clone()));
+
+    first.poll();
+    second.poll();
+    send(&channel);
+
+    let (woken, mut other) = if first.woken() {
+        (first, second)
+    } else {
+        (second, first)
+    };
+
+    assert!(woken.woken(), "{name}: not woken");
+    drop(woken);
+    other.poll_woken();
+    assert!(other.done, "{name}: lost wakeup");
+}
+
+#[test]
+fn channel() {
+    cancel_woken(
+        "channel",
+        Channel::new(),
+        |channel| async move {
+            channel.recv().await.unwrap();
+        },
+        |channel| channel.try_send(()).unwrap(),
+    );
+}
+
+#[test]
+fn lanes() {
+    cancel_woken(
+        "lanes",
+        lanes::Channel::new(2),
+        |channel| async move {
+            channel.recv().await;
+        },
+        |channel| channel.send_lane(1, ()),
+    );
+}
+
+#[test]
+fn priority() {
+    cancel_woken(
+        "priority",
+        priority::Channel::new(),
+        |channel| async move {
+            channel.recv().await;
+        },
+        |channel| channel.send_low(()),
+    );
+}
+
+#[test]
+fn wfq() {
+    cancel_woken(
+        "wfq",
+        wfq::Channel::new(&[1, 2]),
+        |channel| async move {
+            channel.recv().await;
+        },
+        |channel| channel.send_class(1, ()),
+    );
+}
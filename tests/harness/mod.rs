@@ -0,0 +1,109 @@
+//! Wakers and tasks for polling futures by hand under chosen schedules
+
+// Each test uses a different part of the harness
+#![allow(dead_code)]
+
+pub mod operations;
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Wake, Waker},
+};
+
+/// Waker that records whether it was woken
+#[derive(Default)]
+pub struct Flag(AtomicBool);
+
+impl Flag {
+    /// Return true if woken since last cleared
+    pub fn is_set(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Set or clear the flag
+    pub fn set(&self, woken: bool) {
+        self.0.store(woken, Ordering::SeqCst);
+    }
+}
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.set(true);
+    }
+}
+
+/// A future, with the flag set by its waker
+///
+/// The flag starts set, so a task that has never been polled counts as woken.
+pub struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    pub flag: Arc<Flag>,
+    pub done: bool,
+}
+
+impl Task {
+    pub fn new(future: impl Future<Output = ()> + 'static) -> Self {
+        Self {
+            future: Box::pin(future),
+            flag: Arc::new(Flag(AtomicBool::new(true))),
+            done: false,
+        }
+    }
+
+    pub fn poll(&mut self) {
+        let waker = Waker::from(self.flag.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        self.flag.set(false);
+        self.done = self.future.as_mut().poll(&mut cx).is_ready();
+    }
+
+    /// Return true if not done and woken since last polled
+    pub fn woken(&self) -> bool {
+        !self.done && self.flag.is_set()
+    }
+
+    /// Poll if woken, returning true if polled
+    pub fn poll_woken(&mut self) -> bool {
+        let woken = self.woken();
+
+        if woken {
+            self.poll();
+        }
+
+        woken
+    }
+}
+
+/// Xorshift random number generator
+pub struct Rng(u64);
+
+impl Rng {
+    /// Create a generator from a seed, which may be zero
+    pub fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Get a random number less than `below`
+    pub fn next(&mut self, below: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % below as u64) as usize
+    }
+}
+
+/// Get the number of iterations to run from an environment variable, or
+/// `default` if unset, so CI stays fast while longer runs remain possible
+pub fn iterations(var: &str, default: u64) -> u64 {
+    std::env::var(var).map_or(default, |n| n.parse().unwrap())
+}
@@ -0,0 +1,171 @@
+//! Interprets bytes as a sequence of operations on one channel, checking the
+//! results against a model queue, and that no waiting future is left without
+//! being woken
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+use whisk::{Channel, Recv, Send};
+
+use super::Flag;
+
+/// A send or receive in progress
+enum Op<'a> {
+    Send(Send<'a, u32>, u32),
+    Recv(Recv<'a, u32>),
+}
+
+/// An operation in progress, with the two wakers it may be polled with
+struct Pending<'a> {
+    op: Op<'a>,
+    wakers: [Arc<Flag>; 2],
+    /// Waker the operation was last polled with, if polled
+    latest: Option<usize>,
+}
+
+impl Pending<'_> {
+    /// Return true if the last waker the operation was polled with was woken
+    fn woken(&self) -> bool {
+        self.latest.is_some_and(|i| self.wakers[i].is_set())
+    }
+}
+
+/// The model: messages in transit, in order
+struct Model {
+    messages: VecDeque<u32>,
+    capacity: usize,
+}
+
+impl Model {
+    fn push(&mut self, message: u32) {
+        assert!(self.messages.len() < self.capacity, "sent to full channel");
+        self.messages.push_back(message);
+    }
+
+    fn pop(&mut self, message: u32) {
+        assert_eq!(self.messages.pop_front(), Some(message), "out of order");
+    }
+}
+
+/// Poll an operation with one of its wakers, returning true if completed
+fn poll(pending: &mut Pending<'_>, waker: usize, model: &mut Model) -> bool {
+    let flag = &pending.wakers[waker];
+    let waker_ = Waker::from(flag.clone());
+    let mut cx = Context::from_waker(&waker_);
+
+    flag.set(false);
+    pending.latest = Some(waker);
+
+    match &mut pending.op {
+        Op::Send(send, message) => match Pin::new(send).poll(&mut cx) {
+            Poll::Ready(result) => {
+                result.unwrap();
+                model.push(*message);
+                true
+            }
+            Poll::Pending => false,
+        },
+        Op::Recv(recv) => match Pin::new(recv).poll(&mut cx) {
+            Poll::Ready(result) => {
+                model.pop(result.unwrap());
+                true
+            }
+            Poll::Pending => false,
+        },
+    }
+}
+
+/// Run the operations encoded in `data`
+pub fn run(data: &[u8]) {
+    let channel = Channel::new();
+    let mut model = Model {
+        messages: VecDeque::new(),
+        capacity: 1,
+    };
+    let mut pending: Vec<Pending<'_>> = Vec::new();
+    let mut next = 0;
+    let mut bytes = data.iter().copied();
+
+    while let Some(byte) = bytes.next() {
+        let index = usize::from(bytes.next().unwrap_or(0));
+
+        match byte % 8 {
+            // Start sending
+            0 => {
+                pending.push(Pending {
+                    op: Op::Send(channel.send(next), next),
+                    wakers: Default::default(),
+                    latest: None,
+                });
+                next += 1;
+            }
+            // Start receiving
+            1 => pending.push(Pending {
+                op: Op::Recv(channel.recv()),
+                wakers: Default::default(),
+                latest: None,
+            }),
+            // Poll with either waker, replacing the one previously polled with
+            2 | 3 if !pending.is_empty() => {
+                let i = index % pending.len();
+
+                if poll(&mut pending[i], usize::from(byte % 8 - 2), &mut model)
+                {
+                    pending.swap_remove(i);
+                }
+            }
+            // Cancel
+            4 if !pending.is_empty() => {
+                pending.swap_remove(index % pending.len());
+            }
+            // Try sending
+            5 => {
+                let full = model.messages.len() >= model.capacity;
+
+                assert_eq!(channel.try_send(next).is_ok(), !full);
+                if !full {
+                    model.push(next);
+                }
+                next += 1;
+            }
+            // Try receiving
+            6 => match channel.try_recv() {
+                Ok(message) => model.pop(message),
+                Err(_) => assert!(model.messages.is_empty()),
+            },
+            // Change capacity
+            7 => {
+                model.capacity = 1 + index % 4;
+                channel.set_capacity(model.capacity);
+            }
+            _ => {}
+        }
+    }
+
+    // Poll woken operations until none are
+    while let Some(i) = pending.iter().position(Pending::woken) {
+        let waker = pending[i].latest.unwrap();
+
+        if poll(&mut pending[i], waker, &mut model) {
+            pending.swap_remove(i);
+        }
+    }
+
+    // Any operation left waiting must be unable to make progress
+    for pending in pending.iter().filter(|pending| pending.latest.is_some()) {
+        match pending.op {
+            Op::Send(..) => assert!(
+                model.messages.len() >= model.capacity,
+                "lost sender wakeup"
+            ),
+            Op::Recv(..) => {
+                assert!(model.messages.is_empty(), "lost receiver wakeup")
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Runs a second channel operation at the critical point inside the first,
+//! after it finds it can't make progress and right before it registers to be
+//! woken, asserting that no message or wakeup is lost.
+//!
+//! ```sh
+//! cargo test --test interleavings --features test_util
+//! ```
+
+mod harness;
+
+use std::{cell::RefCell, future::Future, rc::Rc};
+
+use harness::Task;
+use whisk::{test_util, Channel};
+
+/// Poll `first`, polling `second` at its critical point, then poll whichever
+/// is woken until neither is
+fn interleave(
+    name: &str,
+    first: impl Future<Output = ()> + 'static,
+    second: impl Future<Output = ()> + 'static,
+) {
+    let mut first = Task::new(first);
+    let second = Rc::new(RefCell::new(Task::new(second)));
+
+    test_util::set_yield_point({
+        let second = second.clone();
+
+        move || second.borrow_mut().poll()
+    });
+    first.poll();
+    test_util::clear_yield_point();
+
+    // Second is still flagged as woken if the yield point wasn't reached
+    let mut second = second.borrow_mut();
+
+    while first.poll_woken() | second.poll_woken() {}
+
+    assert!(first.done && second.done, "{name}: lost wakeup");
+}
+
+#[test]
+fn recv_while_sending() {
+    let channel = Channel::new();
+
+    interleave(
+        "Receive from empty, while sending",
+        {
+            let channel = channel.clone();
+
+            async move { assert_eq!(channel.recv().await, Ok(1)) }
+        },
+        {
+            let channel = channel.clone();
+
+            async move { channel.send(1).await.unwrap() }
+        },
+    );
+}
+
+#[test]
+fn send_while_receiving() {
+    let channel = Channel::new();
+
+    channel.try_send(1).unwrap();
+    interleave(
+        "Send to full, while receiving",
+        {
+            let channel = channel.clone();
+
+            async move { channel.send(2).await.unwrap() }
+        },
+        {
+            let channel = channel.clone();
+
+            async move { assert_eq!(channel.recv().await, Ok(1)) }
+        },
+    );
+    assert_eq!(channel.try_recv(), Ok(2));
+}
+
+#[test]
+fn recv_while_receiver_gives_up() {
+    let channel = Channel::new();
+
+    interleave(
+        "Receive, while another receiver gives up, then sending",
+        {
+            let channel = channel.clone();
+
+            async move { assert_eq!(channel.recv().await, Ok(3)) }
+        },
+        {
+            let channel = channel.clone();
+
+            async move {
+                let _ = futures::poll!(channel.recv());
+
+                channel.send(3).await.unwrap();
+            }
+        },
+    );
+}
+
+#[test]
+fn send_while_sender_gives_up() {
+    let channel = Channel::new();
+
+    channel.try_send(4).unwrap();
+    interleave(
+        "Send to full, while another sender gives up, then receiving",
+        {
+            let channel = channel.clone();
+
+            async move { channel.send(5).await.unwrap() }
+        },
+        {
+            let channel = channel.clone();
+
+            async move {
+                let _ = futures::poll!(channel.send(6));
+
+                assert_eq!(channel.recv().await, Ok(4));
+            }
+        },
+    );
+    assert_eq!(channel.try_recv(), Ok(5));
+}
@@ -0,0 +1,74 @@
+//! Checks every interleaving of the mutex and wake list atomics with loom,
+//! asserting that no message or wakeup is lost.  A lost wakeup leaves every
+//! thread blocked, which loom reports as a deadlock.
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+
+#![cfg(loom)]
+
+use loom::{future::block_on, model::Builder, thread};
+use whisk::Channel;
+
+/// Check `f` under every interleaving with up to `preemptions` preemptions
+///
+/// Three reach each race between a lock, a registration and a wakeup, but
+/// models with more than two threads use fewer to finish in minutes.
+fn model(preemptions: usize, f: impl Fn() + Sync + Send + 'static) {
+    let mut builder = Builder::new();
+
+    builder.preemption_bound = Some(preemptions);
+    builder.check(f);
+}
+
+#[test]
+fn send_wakes_receiver() {
+    model(3, || {
+        let channel = Channel::new();
+        let sender = channel.clone();
+        let thread = thread::spawn(move || block_on(sender.send(1)).unwrap());
+
+        assert_eq!(block_on(channel.recv()), Ok(1));
+        thread.join().unwrap();
+    });
+}
+
+#[test]
+fn recv_wakes_sender() {
+    model(3, || {
+        let channel = Channel::with_capacity(1);
+        let sender = channel.clone();
+        let thread = thread::spawn(move || {
+            block_on(sender.send(1)).unwrap();
+            block_on(sender.send(2)).unwrap();
+        });
+
+        assert_eq!(block_on(channel.recv()), Ok(1));
+        assert_eq!(block_on(channel.recv()), Ok(2));
+        thread.join().unwrap();
+    });
+}
+
+#[test]
+fn senders_contend() {
+    model(2, || {
+        let channel = Channel::with_capacity(1);
+        let threads = [1, 2].map(|message| {
+            let sender = channel.clone();
+
+            thread::spawn(move || block_on(sender.send(message)).unwrap())
+        });
+        let mut received = [
+            block_on(channel.recv()).unwrap(),
+            block_on(channel.recv()).unwrap(),
+        ];
+
+        received.sort_unstable();
+        assert_eq!(received, [1, 2]);
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    });
+}
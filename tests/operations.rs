@@ -0,0 +1,25 @@
+//! Runs random sequences of channel operations against a model queue.
+//!
+//! This is the fuzz target's interpreter with seeded random inputs, so it
+//! runs without a fuzzer.  Set `WHISK_OPERATIONS` to run more sequences than
+//! the default, or fuzz with `cargo fuzz run channel` from the repository
+//! root.
+
+mod harness;
+
+use harness::Rng;
+
+const DEFAULT_SEQUENCES: u64 = 1000;
+
+#[test]
+fn operations() {
+    let sequences = harness::iterations("WHISK_OPERATIONS", DEFAULT_SEQUENCES);
+
+    for seed in 0..sequences {
+        let mut rng = Rng::new(seed);
+        let data: Vec<u8> =
+            (0..rng.next(256)).map(|_| rng.next(256) as u8).collect();
+
+        harness::operations::run(&data);
+    }
+}
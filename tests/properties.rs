@@ -0,0 +1,150 @@
+//! Runs producers and consumers on one channel under schedules generated by
+//! proptest, checking that each consumer receives any one producer's messages
+//! in the order they were sent, that every message is received exactly once,
+//! and that every waiting task is eventually woken.
+//!
+//! Failing schedules are shrunk to a minimal case.  Set `PROPTEST_CASES` to
+//! run more cases than the default.
+
+mod harness;
+
+use std::{cell::RefCell, rc::Rc};
+
+use harness::Task;
+use proptest::prelude::*;
+use whisk::Channel;
+
+/// A generated schedule
+#[derive(Debug, Clone)]
+struct Schedule {
+    producers: usize,
+    consumers: usize,
+    messages: usize,
+    capacity: usize,
+    /// Which task to poll next: true to poll any pending task rather than
+    /// only woken ones, with an index into the candidates
+    choices: Vec<(bool, usize)>,
+}
+
+fn schedule() -> impl Strategy<Value = Schedule> {
+    (
+        1..=4usize,
+        1..=4usize,
+        1..=8usize,
+        1..=3usize,
+        prop::collection::vec(
+            (prop::bool::weighted(0.1), any::<usize>()),
+            0..256,
+        ),
+    )
+        .prop_map(|(producers, consumers, messages, capacity, choices)| {
+            Schedule {
+                producers,
+                consumers,
+                messages,
+                capacity,
+                choices,
+            }
+        })
+}
+
+/// Run a schedule, returning the messages each consumer received in order,
+/// or `None` if tasks were left waiting without being woken
+fn run(schedule: &Schedule) -> Option<Vec<Vec<usize>>> {
+    let Schedule {
+        producers,
+        consumers,
+        messages,
+        capacity,
+        ..
+    } = *schedule;
+    let total = producers * messages;
+    let channel = Channel::new();
+    let received = Rc::new(RefCell::new(vec![Vec::new(); consumers]));
+    let mut tasks = Vec::new();
+
+    channel.set_capacity(capacity);
+
+    for producer in 0..producers {
+        let channel = channel.clone();
+
+        tasks.push(Task::new(async move {
+            for i in 0..messages {
+                channel.send(producer * messages + i).await.unwrap();
+            }
+        }));
+    }
+
+    for consumer in 0..consumers {
+        let (channel, received) = (channel.clone(), received.clone());
+
+        tasks.push(Task::new(async move {
+            while let Ok(message) = channel.recv().await {
+                let mut received = received.borrow_mut();
+
+                received[consumer].push(message);
+
+                // Close once the last message is received
+                if received.iter().map(Vec::len).sum::<usize>() == total {
+                    channel.close();
+                }
+            }
+        }));
+    }
+
+    let mut choices = schedule.choices.iter().copied();
+
+    loop {
+        let pending = tasks.iter().filter(|task| !task.done).count();
+        let woken = tasks.iter().filter(|task| task.woken()).count();
+
+        if woken == 0 {
+            if pending != 0 {
+                return None;
+            }
+
+            break;
+        }
+
+        // Once out of choices, poll the first woken task
+        let (spurious, index) = choices.next().unwrap_or((false, 0));
+        let index = index % if spurious { pending } else { woken };
+        let mut candidates = tasks
+            .iter_mut()
+            .filter(|task| !task.done && (spurious || task.flag.is_set()));
+
+        candidates.nth(index).unwrap().poll();
+    }
+
+    Some(received.take())
+}
+
+proptest! {
+    #[test]
+    fn delivery(schedule in schedule()) {
+        let received = run(&schedule);
+
+        prop_assert!(received.is_some(), "lost wakeup");
+
+        let received = received.unwrap();
+        let mut all: Vec<usize> = received.concat();
+
+        all.sort_unstable();
+        prop_assert_eq!(
+            all,
+            (0..schedule.producers * schedule.messages).collect::<Vec<_>>(),
+            "not received exactly once",
+        );
+
+        for messages in received {
+            let mut last = vec![None; schedule.producers];
+
+            for message in messages {
+                let last = &mut last[message / schedule.messages];
+
+                prop_assert!(*last < Some(message), "out of order");
+                *last = Some(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Runs many producers and consumers on one channel under randomized
+//! schedules, asserting that every message is received exactly once and in
+//! the order its producer sent it, and that no task is left waiting without
+//! being woken.
+//!
+//! Each schedule polls woken tasks in a random order from a seeded generator,
+//! with occasional spurious polls, so a failing seed can be replayed.  Set
+//! `WHISK_SCHEDULES` to run more schedules than the default:
+//!
+//! ```sh
+//! WHISK_SCHEDULES=100000 cargo test --release --test schedules
+//! ```
+
+mod harness;
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use harness::{Rng, Task};
+use whisk::Channel;
+
+const DEFAULT_SCHEDULES: u64 = 1000;
+const MESSAGES: usize = 8;
+
+fn schedule(seed: u64) {
+    let mut rng = Rng::new(seed);
+    let producers = 1 + rng.next(4);
+    let consumers = 1 + rng.next(4);
+    let total = producers * MESSAGES;
+    let channel = Channel::new();
+    let seen = Rc::new(RefCell::new(vec![false; total]));
+    let count = Rc::new(Cell::new(0));
+    let last = Rc::new(RefCell::new(vec![None; producers]));
+    let mut tasks = Vec::new();
+
+    channel.set_capacity(1 + rng.next(3));
+
+    for producer in 0..producers {
+        let channel = channel.clone();
+
+        tasks.push(Task::new(async move {
+            for i in 0..MESSAGES {
+                channel.send(producer * MESSAGES + i).await.unwrap();
+            }
+        }));
+    }
+
+    for _ in 0..consumers {
+        let (channel, seen, count, last) =
+            (channel.clone(), seen.clone(), count.clone(), last.clone());
+
+        tasks.push(Task::new(async move {
+            while let Ok(message) = channel.recv().await {
+                let mut seen = seen.borrow_mut();
+
+                let last = &mut last.borrow_mut()[message / MESSAGES];
+
+                assert!(!seen[message], "seed {seed}: received twice");
+                assert!(*last < Some(message), "seed {seed}: out of order");
+                seen[message] = true;
+                *last = Some(message);
+                count.set(count.get() + 1);
+
+                // Close once the last message is seen
+                if count.get() == total {
+                    channel.close();
+                }
+            }
+        }));
+    }
+
+    loop {
+        let pending = tasks.iter().filter(|task| !task.done).count();
+        let woken = tasks.iter().filter(|task| task.woken()).count();
+
+        if woken == 0 {
+            assert_eq!(pending, 0, "seed {seed}: lost wakeup");
+            break;
+        }
+
+        // Usually poll a woken task, but sometimes any pending task
+        let spurious = rng.next(8) == 0;
+        let index = rng.next(if spurious { pending } else { woken });
+        let mut candidates = tasks
+            .iter_mut()
+            .filter(|task| !task.done && (spurious || task.flag.is_set()));
+
+        candidates.nth(index).unwrap().poll();
+    }
+
+    assert_eq!(count.get(), total, "seed {seed}: lost message");
+}
+
+#[test]
+fn schedules() {
+    let schedules = harness::iterations("WHISK_SCHEDULES", DEFAULT_SCHEDULES);
+
+    for seed in 0..schedules {
+        schedule(seed);
+    }
+}